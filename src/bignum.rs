@@ -0,0 +1,122 @@
+//! Bignum:
+//! This module has a small unsigned big integer, for counts that outgrow
+//! `u128`. It only has what the days need, addition, multiplication and
+//! printing in decimal. The limbs are `u64`, least significant first, with the
+//! products taken in `u128`.
+
+use std::ops::{Add, Mul};
+
+// Largest power of 10 in a `u64`, for printing
+const DECIMAL_BASE: u64 = 10_000_000_000_000_000_000;
+const DECIMAL_DIGITS: usize = 19;
+
+// -----------------------------------------------------------------------------
+// Big unsigned integer data struct
+// -----------------------------------------------------------------------------
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct BigUint {
+    // No trailing zero limbs, so zero has no limbs
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    fn trim(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    // Divide in place by a small divisor, returning the remainder
+    fn divide_small(&mut self, divisor: u64) -> u64 {
+        let mut remainder = 0_u128;
+        self.limbs.iter_mut().rev().for_each(|limb| {
+            let value = (remainder << 64) | *limb as u128;
+            *limb = (value / divisor as u128) as u64;
+            remainder = value % divisor as u128;
+        });
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        remainder as u64
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        Self {
+            limbs: vec![value as u64, (value >> 64) as u64],
+        }
+        .trim()
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+    fn add(self, other: &BigUint) -> BigUint {
+        let (long, short) = if self.limbs.len() >= other.limbs.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut carry = false;
+        let mut limbs: Vec<u64> = long
+            .limbs
+            .iter()
+            .enumerate()
+            .map(|(i, &limb)| {
+                let (sum, overflow_1) = limb.overflowing_add(*short.limbs.get(i).unwrap_or(&0));
+                let (sum, overflow_2) = sum.overflowing_add(carry as u64);
+                carry = overflow_1 || overflow_2;
+                sum
+            })
+            .collect();
+        if carry {
+            limbs.push(1);
+        }
+        BigUint { limbs }
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::default();
+        }
+        let mut limbs = vec![0_u64; self.limbs.len() + other.limbs.len()];
+        self.limbs.iter().enumerate().for_each(|(i, &a)| {
+            let mut carry = 0_u128;
+            other.limbs.iter().enumerate().for_each(|(j, &b)| {
+                let value = a as u128 * b as u128 + limbs[i + j] as u128 + carry;
+                limbs[i + j] = value as u64;
+                carry = value >> 64;
+            });
+            limbs[i + other.limbs.len()] = carry as u64;
+        });
+        BigUint { limbs }.trim()
+    }
+}
+
+impl std::fmt::Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off 19 decimal digits at a time
+        let mut value = self.clone();
+        let mut chunks = vec![];
+        while !value.is_zero() {
+            chunks.push(value.divide_small(DECIMAL_BASE));
+        }
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap_or(&0))?;
+        chunks.try_for_each(|chunk| write!(f, "{:0width$}", chunk, width = DECIMAL_DIGITS))
+    }
+}
+
+// -----------------------------------------------------------------------------
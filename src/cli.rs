@@ -0,0 +1,159 @@
+//! CLI:
+//! This module parses the command line arguments for the driver. With no
+//! arguments every day is run and timed, as before. A single day can also be
+//! run on its own with day specific options, such as extra visualizations.
+
+// Constants
+const LAST_PUZZLE_DAY: usize = 25;
+const USAGE: &str = "usage: advent2021 [day DAY [INPUT] [--OPTION[=VALUE] ...] | \
+                     fetch [DAY ...] [--force] | submit DAY PART ANSWER]";
+
+// -----------------------------------------------------------------------------
+// Command enum
+// -----------------------------------------------------------------------------
+#[derive(Debug, PartialEq)]
+pub(crate) enum Command {
+    Run,
+    Day {
+        day: usize,
+        input: Option<String>,
+        options: Options,
+    },
+    Fetch {
+        days: Vec<usize>,
+        force: bool,
+    },
+    Submit {
+        day: usize,
+        part: usize,
+        answer: String,
+    },
+}
+
+// -----------------------------------------------------------------------------
+// Options data struct
+// -----------------------------------------------------------------------------
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Options {
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Options {
+    // Reject any option the day does not know about
+    pub(crate) fn check(&self, known: &[&str]) -> crate::Result<()> {
+        match self
+            .options
+            .iter()
+            .find(|(name, _)| !known.contains(&name.as_str()))
+        {
+            Some((name, _)) => Err(crate::Error {
+                message: format!("unknown option '--{}', expected one of {:?}", name, known),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    pub(crate) fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(option, _)| option == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    pub(crate) fn parse<T>(&self, name: &str) -> crate::Result<Option<T>>
+    where
+        T: std::str::FromStr,
+        crate::Error: From<T::Err>,
+    {
+        match self.value(name) {
+            Some(value) => Ok(Some(value.parse()?)),
+            None if self.flag(name) => Err(crate::Error {
+                message: format!("option '--{}' needs a value, use '--{}=VALUE'", name, name),
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Argument helpers
+// -----------------------------------------------------------------------------
+fn usage_error(message: &str) -> crate::Error {
+    crate::Error {
+        message: format!("{}\n{}", message, USAGE),
+    }
+}
+
+fn parse_day(argument: &str) -> crate::Result<usize> {
+    let day: usize = argument.parse()?;
+    if day == 0 || day > LAST_PUZZLE_DAY {
+        return Err(usage_error(&format!("invalid day {}", day)));
+    }
+    Ok(day)
+}
+
+// -----------------------------------------------------------------------------
+// Parse arguments
+// -----------------------------------------------------------------------------
+pub(crate) fn parse(mut args: impl Iterator<Item = String>) -> crate::Result<Command> {
+    match args.next().as_deref() {
+        None => Ok(Command::Run),
+        Some("day") => {
+            let day = parse_day(&args.next().ok_or_else(|| usage_error("missing day"))?)?;
+            let mut input = None;
+            let mut options = Options::default();
+            for argument in args {
+                if let Some(option) = argument.strip_prefix("--") {
+                    let (name, value) = match option.split_once('=') {
+                        Some((name, value)) => (name, Some(value.to_string())),
+                        None => (option, None),
+                    };
+                    options.options.push((name.to_string(), value));
+                } else if input.is_none() {
+                    input = Some(argument);
+                } else {
+                    return Err(usage_error("too many arguments"));
+                }
+            }
+            Ok(Command::Day {
+                day,
+                input,
+                options,
+            })
+        }
+        Some("fetch") => {
+            let mut days = vec![];
+            let mut force = false;
+            for argument in args {
+                if argument == "--force" {
+                    force = true;
+                } else {
+                    days.push(parse_day(&argument)?);
+                }
+            }
+            Ok(Command::Fetch { days, force })
+        }
+        Some("submit") => {
+            let day = parse_day(&args.next().ok_or_else(|| usage_error("missing day"))?)?;
+            let part: usize = args
+                .next()
+                .ok_or_else(|| usage_error("missing part"))?
+                .parse()?;
+            if part != 1 && part != 2 {
+                return Err(usage_error(&format!("invalid part {}", part)));
+            }
+            let answer = args.next().ok_or_else(|| usage_error("missing answer"))?;
+            if args.next().is_some() {
+                return Err(usage_error("too many arguments"));
+            }
+            Ok(Command::Submit { day, part, answer })
+        }
+        Some(other) => Err(usage_error(&format!("unknown command '{}'", other))),
+    }
+}
+
+// -----------------------------------------------------------------------------
@@ -1,27 +1,182 @@
 //! Day 1:
-//! This solution uses a sliding window of 2 values for the first part. In the
-//! second part we expand the window to 4 values as
-//! `[a, b, c].sum() - [b, c, d].sum() == a - d`, so we only need to consider
-//! the first and last values to determine if the three value windows increase
-//! or decrease.
+//! This solution compares sliding windows of depths. As
+//! `[a, b, c].sum() - [b, c, d].sum() == a - d`, we only need to consider
+//! the first and last values to determine if two neighbouring windows increase
+//! or decrease. The same holds for any window length `k`, so a depth only needs
+//! to be compared with the depth `k` places earlier. Keeping the last `k`
+//! depths in a ring buffer lets this run on a stream of depths.
+//! The depths can also be profiled, with the longest run of increases, the
+//! largest single jump, and a moving average curve.
 
+use crate::cli::Options;
 use crate::prelude::*;
 
+pub(crate) const OPTIONS: &[&str] = &["window", "analyze", "average", "svg"];
+const DEFAULT_AVERAGE_WINDOW: usize = 3;
+const NUMBER_COLUMNS: usize = 72;
+
+// -----------------------------------------------------------------------------
+// Count increases between sliding windows of a given length
+// -----------------------------------------------------------------------------
+pub(crate) fn count_increases(
+    depths: impl IntoIterator<Item = i32>,
+    window: usize,
+) -> crate::Result<usize> {
+    // Short windows get a fixed size ring the compiler can keep in registers
+    match window {
+        0 => Err(crate::Error {
+            message: "window length must be at least 1".to_string(),
+        }),
+        1 => Ok(count_increases_ring(depths, &mut [0; 1])),
+        2 => Ok(count_increases_ring(depths, &mut [0; 2])),
+        3 => Ok(count_increases_ring(depths, &mut [0; 3])),
+        4 => Ok(count_increases_ring(depths, &mut [0; 4])),
+        _ => Ok(count_increases_ring(depths, &mut vec![0; window])),
+    }
+}
+
+fn count_increases_ring(depths: impl IntoIterator<Item = i32>, previous: &mut [i32]) -> usize {
+    let window = previous.len();
+    let mut depths = depths.into_iter();
+    for slot in previous.iter_mut() {
+        match depths.next() {
+            Some(depth) => *slot = depth,
+            None => return 0,
+        }
+    }
+    let mut position = 0;
+    let mut count = 0;
+    for depth in depths {
+        count += (depth > previous[position]) as usize;
+        previous[position] = depth;
+        position = if position + 1 == window {
+            0
+        } else {
+            position + 1
+        };
+    }
+    count
+}
+
 // -----------------------------------------------------------------------------
 // Part 1
 // -----------------------------------------------------------------------------
-fn part_1(depths: &Vec<i32>) -> crate::Result<i32> {
-    Ok(depths.windows(2).filter(|pair| pair[1] > pair[0]).count() as i32)
+fn part_1(depths: &[i32]) -> crate::Result<i32> {
+    Ok(count_increases(depths.iter().copied(), 1)? as i32)
 }
 
 // -----------------------------------------------------------------------------
 // Part 2
 // -----------------------------------------------------------------------------
-fn part_2(depths: &Vec<i32>) -> crate::Result<i32> {
-    Ok(depths
-        .windows(4)
-        .filter(|quartet| quartet[3] > quartet[0])
-        .count() as i32)
+fn part_2(depths: &[i32]) -> crate::Result<i32> {
+    Ok(count_increases(depths.iter().copied(), 3)? as i32)
+}
+
+// -----------------------------------------------------------------------------
+// Depth profile data struct
+// -----------------------------------------------------------------------------
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Profile {
+    // Index of the first depth and number of consecutive increases
+    pub(crate) longest_run: (usize, usize),
+    // Index of the depth before the jump and signed size of the jump
+    pub(crate) largest_jump: (usize, i64),
+    pub(crate) moving_average: Vec<f64>,
+}
+
+pub(crate) fn analyze(depths: &[i32], window: usize) -> crate::Result<Profile> {
+    if window == 0 {
+        return Err(crate::Error {
+            message: "window length must be at least 1".to_string(),
+        });
+    }
+    if depths.len() < 2 {
+        return Err(crate::Error {
+            message: "profile needs at least two depths".to_string(),
+        });
+    }
+    let mut profile = Profile::default();
+    let mut run = (0, 0);
+    depths.windows(2).enumerate().for_each(|(i, pair)| {
+        let jump = pair[1] as i64 - pair[0] as i64;
+        if jump > 0 {
+            run = if run.1 == 0 {
+                (i, 1)
+            } else {
+                (run.0, run.1 + 1)
+            };
+            if run.1 > profile.longest_run.1 {
+                profile.longest_run = run;
+            }
+        } else {
+            run = (i + 1, 0);
+        }
+        if jump.abs() > profile.largest_jump.1.abs() {
+            profile.largest_jump = (i, jump);
+        }
+    });
+    profile.moving_average = depths
+        .windows(window)
+        .map(|values| values.iter().map(|&value| value as i64).sum::<i64>() as f64 / window as f64)
+        .collect();
+    Ok(profile)
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    if let Some(window) = options.parse("window")? {
+        // Stream the depths straight from the buffer
+        let increases = itertools::process_results(
+            buffer.lines().map(|line| line.trim().parse::<i32>()),
+            |depths| count_increases(depths, window),
+        )??;
+        println!("    {}:", format!("Window {}", window).purple().bold());
+        println!("      📉 Increase: {}", increases);
+    }
+    let svg = options.value("svg");
+    if options.flag("analyze") || svg.is_some() {
+        let depths: Vec<i32> = buffer
+            .lines()
+            .map(|line| line.trim().parse())
+            .collect::<Result<_, _>>()?;
+        let window = options.parse("average")?.unwrap_or(DEFAULT_AVERAGE_WINDOW);
+        let profile = analyze(&depths, window)?;
+        let (start, length) = profile.longest_run;
+        let (before, jump) = profile.largest_jump;
+        println!("    {}:", "Profile".purple().bold());
+        println!(
+            "      Longest increase: {} in a row, from line {} ({}) to line {} ({})",
+            length,
+            start + 1,
+            depths[start],
+            start + length + 1,
+            depths[start + length]
+        );
+        println!(
+            "      Largest jump: {:+}, from line {} ({}) to line {} ({})",
+            jump,
+            before + 1,
+            depths[before],
+            before + 2,
+            depths[before + 1]
+        );
+        println!("      Moving average ({} values):", window);
+        println!(
+            "      {}",
+            output::sparkline(&profile.moving_average, NUMBER_COLUMNS).blue()
+        );
+        if let Some(path) = svg {
+            let raw: Vec<f64> = depths.iter().map(|depth| *depth as f64).collect();
+            output::write_svg_plot(
+                path,
+                "Sonar sweep depth profile",
+                &[("#bbbbbb", &raw), ("#1f77b4", &profile.moving_average)],
+            )?;
+        }
+    }
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -35,8 +190,8 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     let start_setup = Instant::now();
     let depths: Vec<i32> = buffer
         .lines()
-        .map(|line| line.parse().expect("failed to parse line"))
-        .collect();
+        .map(|line| line.trim().parse())
+        .collect::<Result<_, _>>()?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -1,81 +1,373 @@
 //! Day 2:
 //! The main part of the effort for this solution is reading the input into a
-//! correct structure. From there following the computations are straightforward.
-//! In the second part, I leveraged the fact that one of the two parts of the
-//! Direction is zero to avoid branching logic, making the code take half as
-//! long as the branching version of the `fold`.
+//! correct structure. The input is parsed into a small command language, and an
+//! interpreter feeds the commands to anything that implements the `Submarine`
+//! trait. Part 1 and part 2 are two submarines that only differ in how they
+//! respond to moving forward and down, so new commands only need a new
+//! `Command` variant and a line in the interpreter. Wrapping a submarine in a
+//! `Tracer` records every position along the way, for plotting the path.
+//! The moves are checked, so a position that overflows is an error, and a
+//! budget of steps stops nested `repeat` blocks from running forever.
 
+use crate::cli::Options;
 use crate::prelude::*;
 
+pub(crate) const OPTIONS: &[&str] = &["trace", "csv", "part"];
+const NUMBER_COLUMNS: usize = 72;
+const NUMBER_ROWS: usize = 16;
+// Commands and repeat passes one program may run, so loops cannot hang
+const MAX_STEPS: usize = 10_000_000;
+
+// -----------------------------------------------------------------------------
+// Command enum
+// -----------------------------------------------------------------------------
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Command {
+    Forward(i32),
+    Down(i32),
+    Up(i32),
+    Back(i32),
+    Reset,
+    Repeat { count: usize, body: Vec<Command> },
+}
+
+fn line_error(number: usize, message: &str) -> crate::Error {
+    crate::Error {
+        message: format!("line {}: {}", number, message),
+    }
+}
+
+// Parse one command per line, with `repeat N` ... `end` blocks
+pub(crate) fn parse_program(buffer: &str) -> crate::Result<Vec<Command>> {
+    // Open repeat blocks, with the line they started on
+    let mut blocks: Vec<(usize, usize, Vec<Command>)> = vec![(0, 1, vec![])];
+    for (i, line) in buffer.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (name, argument) = match line.split_once(' ') {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (line, None),
+        };
+        let value = || -> crate::Result<i32> {
+            argument
+                .ok_or_else(|| line_error(number, &format!("'{}' needs a value", name)))?
+                .parse()
+                .map_err(|_| line_error(number, &format!("invalid value in '{}'", line)))
+        };
+        let command = match name {
+            "forward" => Command::Forward(value()?),
+            "down" => Command::Down(value()?),
+            "up" => Command::Up(value()?),
+            "back" => Command::Back(value()?),
+            "reset" => Command::Reset,
+            "repeat" => {
+                let count = value()?;
+                if count < 0 {
+                    return Err(line_error(number, "repeat count must not be negative"));
+                }
+                blocks.push((number, count as usize, vec![]));
+                continue;
+            }
+            "end" => {
+                if blocks.len() == 1 {
+                    return Err(line_error(number, "'end' without 'repeat'"));
+                }
+                let (_, count, body) = blocks.pop().expect("no open block");
+                Command::Repeat { count, body }
+            }
+            _ => return Err(line_error(number, &format!("unknown command '{}'", name))),
+        };
+        blocks.last_mut().expect("no open block").2.push(command);
+    }
+    if blocks.len() > 1 {
+        let (number, _, _) = blocks.last().expect("no open block");
+        return Err(line_error(*number, "'repeat' without 'end'"));
+    }
+    Ok(blocks.pop().expect("no open block").2)
+}
+
+// -----------------------------------------------------------------------------
+// Submarine trait
+// -----------------------------------------------------------------------------
+pub(crate) trait Submarine: Default {
+    // Moves are None when the position overflows
+    fn forward(&mut self, distance: i32) -> Option<()>;
+    fn down(&mut self, distance: i32) -> Option<()>;
+    fn horizontal(&self) -> i32;
+    fn depth(&self) -> i32;
+
+    fn aim(&self) -> i32 {
+        0
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn product(&self) -> i64 {
+        self.horizontal() as i64 * self.depth() as i64
+    }
+
+    fn execute(&mut self, program: &[Command]) -> crate::Result<()> {
+        interpret(self, program, &mut 0)
+    }
+}
+
+// Interpreter, counting steps against the budget
+fn interpret<S: Submarine>(
+    submarine: &mut S,
+    program: &[Command],
+    steps: &mut usize,
+) -> crate::Result<()> {
+    for command in program.iter() {
+        *steps += 1;
+        if *steps > MAX_STEPS {
+            return Err(crate::Error {
+                message: format!("program runs more than {} steps", MAX_STEPS),
+            });
+        }
+        let moved = match command {
+            Command::Forward(distance) => submarine.forward(*distance),
+            Command::Down(distance) => submarine.down(*distance),
+            Command::Up(distance) => distance
+                .checked_neg()
+                .and_then(|distance| submarine.down(distance)),
+            Command::Back(distance) => distance
+                .checked_neg()
+                .and_then(|distance| submarine.forward(distance)),
+            Command::Reset => {
+                submarine.reset();
+                Some(())
+            }
+            Command::Repeat { count, body } => {
+                for _ in 0..*count {
+                    *steps += 1;
+                    interpret(submarine, body, steps)?;
+                }
+                Some(())
+            }
+        };
+        if moved.is_none() {
+            return Err(crate::Error {
+                message: "submarine position overflows".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
 // -----------------------------------------------------------------------------
-// Direction data struct
+// Part 1
 // -----------------------------------------------------------------------------
 #[derive(Default, Debug)]
-struct Direction {
+pub(crate) struct Pilot {
     horizontal: i32, // positive is forward
-    vertical: i32,   // positive is down
-}
-
-impl std::str::FromStr for Direction {
-    type Err = crate::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut line = s.splitn(2, ' ');
-        match line.next().expect("failed to parse command") {
-            "forward" => Ok(Self {
-                horizontal: s[8..].parse()?,
-                vertical: 0,
-            }),
-            "down" => Ok(Self {
-                horizontal: 0,
-                vertical: s[5..].parse()?,
-            }),
-            "up" => Ok(Self {
-                horizontal: 0,
-                vertical: -s[3..].parse()?,
-            }),
-            _ => Err(crate::Error {
-                message: "invalid direction".to_string(),
-            }),
-        }
+    depth: i32,      // positive is down
+}
+
+impl Submarine for Pilot {
+    fn forward(&mut self, distance: i32) -> Option<()> {
+        self.horizontal = self.horizontal.checked_add(distance)?;
+        Some(())
+    }
+
+    fn down(&mut self, distance: i32) -> Option<()> {
+        self.depth = self.depth.checked_add(distance)?;
+        Some(())
+    }
+
+    fn horizontal(&self) -> i32 {
+        self.horizontal
+    }
+
+    fn depth(&self) -> i32 {
+        self.depth
     }
 }
 
 // -----------------------------------------------------------------------------
-// Direction with Aim data struct
+// Part 2
 // -----------------------------------------------------------------------------
 #[derive(Default, Debug)]
-struct AimedDirection {
+pub(crate) struct AimedPilot {
     aim: i32,
     horizontal: i32, // positive is forward
-    vertical: i32,   // positive is down
+    depth: i32,      // positive is down
+}
+
+impl Submarine for AimedPilot {
+    fn forward(&mut self, distance: i32) -> Option<()> {
+        self.horizontal = self.horizontal.checked_add(distance)?; // increase horizontal by X
+        self.depth = self.depth.checked_add(self.aim.checked_mul(distance)?)?; // increase depth by aim * X
+        Some(())
+    }
+
+    fn down(&mut self, distance: i32) -> Option<()> {
+        self.aim = self.aim.checked_add(distance)?; // increase aim by Y
+        Some(())
+    }
+
+    fn horizontal(&self) -> i32 {
+        self.horizontal
+    }
+
+    fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    fn aim(&self) -> i32 {
+        self.aim
+    }
+}
+
+pub(crate) fn pilot<S: Submarine>(program: &[Command]) -> crate::Result<S> {
+    let mut submarine = S::default();
+    submarine.execute(program)?;
+    Ok(submarine)
 }
 
 // -----------------------------------------------------------------------------
-// Part 1
+// Trace of positions
 // -----------------------------------------------------------------------------
-fn part_1(directions: &Vec<Direction>) -> crate::Result<Direction> {
-    Ok(directions
-        .iter()
-        .fold(Direction::default(), |mut acc, curr| {
-            acc.horizontal += curr.horizontal;
-            acc.vertical += curr.vertical;
-            acc
-        }))
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct State {
+    pub(crate) horizontal: i32,
+    pub(crate) depth: i32,
+    pub(crate) aim: i32,
+}
+
+#[derive(Default, Debug)]
+pub(crate) struct Tracer<S> {
+    submarine: S,
+    states: Vec<State>,
+}
+
+impl<S: Submarine> Tracer<S> {
+    fn record(&mut self) {
+        self.states.push(State {
+            horizontal: self.submarine.horizontal(),
+            depth: self.submarine.depth(),
+            aim: self.submarine.aim(),
+        });
+    }
+}
+
+impl<S: Submarine> Submarine for Tracer<S> {
+    fn forward(&mut self, distance: i32) -> Option<()> {
+        self.submarine.forward(distance)?;
+        self.record();
+        Some(())
+    }
+
+    fn down(&mut self, distance: i32) -> Option<()> {
+        self.submarine.down(distance)?;
+        self.record();
+        Some(())
+    }
+
+    fn horizontal(&self) -> i32 {
+        self.submarine.horizontal()
+    }
+
+    fn depth(&self) -> i32 {
+        self.submarine.depth()
+    }
+
+    fn aim(&self) -> i32 {
+        self.submarine.aim()
+    }
+
+    fn reset(&mut self) {
+        self.submarine.reset();
+        self.record();
+    }
+}
+
+// Every state from the start to the end of the program
+pub(crate) fn trace<S: Submarine>(program: &[Command]) -> crate::Result<Vec<State>> {
+    let mut tracer = Tracer::<S>::default();
+    tracer.record();
+    tracer.execute(program)?;
+    Ok(tracer.states)
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Summary {
+    pub(crate) max_depth: i32,
+    pub(crate) last: State,
+    pub(crate) distance: f64,
+}
+
+pub(crate) fn summarize(states: &[State]) -> Summary {
+    Summary {
+        max_depth: states.iter().map(|state| state.depth).max().unwrap_or(0),
+        last: states.last().copied().unwrap_or_default(),
+        // Length of the path through (horizontal, depth)
+        distance: states
+            .windows(2)
+            .map(|pair| {
+                let horizontal = pair[1].horizontal as f64 - pair[0].horizontal as f64;
+                let depth = pair[1].depth as f64 - pair[0].depth as f64;
+                horizontal.hypot(depth)
+            })
+            .sum(),
+    }
 }
 
 // -----------------------------------------------------------------------------
-// Part 2
+// Extras
 // -----------------------------------------------------------------------------
-fn part_2(directions: &Vec<Direction>) -> crate::Result<AimedDirection> {
-    Ok(directions
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    let csv = options.value("csv");
+    if !options.flag("trace") && csv.is_none() {
+        return Ok(());
+    }
+    let program = parse_program(buffer)?;
+    let part = options.parse("part")?.unwrap_or(2);
+    let states = match part {
+        1 => trace::<Pilot>(&program)?,
+        2 => trace::<AimedPilot>(&program)?,
+        _ => {
+            return Err(crate::Error {
+                message: format!("invalid part {}", part),
+            })
+        }
+    };
+    let summary = summarize(&states);
+    println!("    {}:", format!("Trace part {}", part).purple().bold());
+    println!("      Steps: {}", states.len() - 1);
+    println!("      Max depth: {}", summary.max_depth);
+    println!(
+        "      Final position: {} forward, {} down",
+        summary.last.horizontal, summary.last.depth
+    );
+    println!("      Total distance: {:.1}", summary.distance);
+    let points: Vec<(f64, f64)> = states
         .iter()
-        .fold(AimedDirection::default(), |mut acc, curr| {
-            // One direction is always zero, so can safely do both paths
-            acc.aim += curr.vertical; // increase aim by Y
-            acc.horizontal += curr.horizontal; // increase horizontal by X
-            acc.vertical += acc.aim * curr.horizontal; // increase depth by aim * X
-            acc
-        }))
+        .map(|state| (state.horizontal as f64, state.depth as f64))
+        .collect();
+    output::ascii_plot(&points, NUMBER_COLUMNS, NUMBER_ROWS)
+        .iter()
+        .for_each(|line| println!("      {}", line.blue()));
+    if let Some(path) = csv {
+        let rows: Vec<Vec<String>> = states
+            .iter()
+            .enumerate()
+            .map(|(step, state)| {
+                vec![
+                    step.to_string(),
+                    state.horizontal.to_string(),
+                    state.depth.to_string(),
+                    state.aim.to_string(),
+                ]
+            })
+            .collect();
+        output::write_csv(path, &["step", "horizontal", "depth", "aim"], &rows)?;
+    }
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -85,12 +377,9 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Setup
     // -------------------------------------------------------------------------
-    // Read to vector
+    // Read to program
     let start_setup = Instant::now();
-    let directions: Vec<Direction> = buffer
-        .lines()
-        .map(|line| line.parse().expect("failed to parse line"))
-        .collect();
+    let program = parse_program(&buffer)?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -98,8 +387,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Sum coordinates
     let start_part_1 = Instant::now();
-    let directions_1 = part_1(&directions)?;
-    let product_1 = directions_1.horizontal * directions_1.vertical;
+    let product_1 = pilot::<Pilot>(&program)?.product();
     let time_part_1 = start_part_1.elapsed();
 
     // -------------------------------------------------------------------------
@@ -107,8 +395,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Compute coordinates with aimed directions
     let start_part_2 = Instant::now();
-    let directions_2 = part_2(&directions)?;
-    let product_2 = directions_2.horizontal * directions_2.vertical;
+    let product_2 = pilot::<AimedPilot>(&program)?.product();
     let time_part_2 = start_part_2.elapsed();
 
     // -------------------------------------------------------------------------
@@ -1,85 +1,162 @@
 //! Day 3:
-//! I'm not entirely happy with this solution because it feel very cumbersome.
-//! The first part is messy because it is faster to count all bytes at the same
-//! time. For the second part, partitioning seems the most straightforward
-//! approach, but I have a lot of code duplication.
+//! The report is read into `u128` words, so any width up to 128 bits works.
+//! For the first part, the column counts are kept bit-sliced, with one word per
+//! bit of the count, so adding a line to every column at once is a ripple carry
+//! across a handful of words. The most common bits then come from comparing the
+//! sliced counts against half the number of lines, again for all columns at
+//! once. For the second part, the sorted report means every candidate range
+//! splits into lines with a zero followed by lines with a one at each bit, so
+//! filtering is just a binary search that narrows the range.
 
 use crate::prelude::*;
 
+// Constants
+const MAX_WIDTH: usize = 128;
+
 // -----------------------------------------------------------------------------
-// Part 1
+// Report data struct
 // -----------------------------------------------------------------------------
-fn part_1(report: &Vec<&str>) -> crate::Result<i64> {
-    let num_entries = report.len();
-    let entry_size = report[0].len();
-    let counts = report.iter().fold(vec![0; entry_size], |mut acc, &curr| {
-        curr.as_bytes()
-            .iter()
+#[derive(Debug)]
+pub(crate) struct Report {
+    width: usize,
+    values: Vec<u128>,
+}
+
+impl std::str::FromStr for Report {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s
+            .lines()
+            .map(|line| line.trim())
             .enumerate()
-            .for_each(|(i, b)| acc[i] += (b - b'0') as i64);
-        acc
-    });
-    let counts_to_bytes: Vec<u8> = counts
-        .iter()
-        .map(|&count| {
-            if count as usize > num_entries / 2 {
-                b'1'
-            } else {
-                b'0'
-            }
+            .filter(|(_, line)| !line.is_empty());
+        let width = lines.clone().next().map_or(0, |(_, line)| line.len());
+        if width == 0 || width > MAX_WIDTH {
+            return Err(crate::Error {
+                message: format!("report width must be from 1 to {} bits", MAX_WIDTH),
+            });
+        }
+        let values = lines
+            .map(|(i, line)| {
+                if line.len() != width {
+                    return Err(crate::Error {
+                        message: format!(
+                            "line {} has {} bits, expected {}",
+                            i + 1,
+                            line.len(),
+                            width
+                        ),
+                    });
+                }
+                line.bytes().try_fold(0, |value, b| match b {
+                    b'0' | b'1' => Ok((value << 1) | (b - b'0') as u128),
+                    _ => Err(crate::Error {
+                        message: format!("invalid bit '{}' on line {}", b as char, i + 1),
+                    }),
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(Self { width, values })
+    }
+}
+
+impl Report {
+    fn mask(&self) -> u128 {
+        u128::MAX >> (MAX_WIDTH - self.width)
+    }
+}
+
+fn product(first: u128, second: u128) -> crate::Result<i64> {
+    first
+        .checked_mul(second)
+        .filter(|product| *product <= i64::MAX as u128)
+        .map(|product| product as i64)
+        .ok_or(crate::Error {
+            message: format!("product of {} and {} is too large", first, second),
         })
-        .collect();
-    let most_common = i64::from_str_radix(&String::from_utf8(counts_to_bytes.clone())?, 2)?;
-    let least_common = i64::from_str_radix(
-        &String::from_utf8(
-            counts_to_bytes
-                .iter()
-                .map(|b| if *b == b'0' { b'1' } else { b'0' })
-                .collect(),
-        )?,
-        2,
-    )?;
-    Ok(most_common * least_common)
 }
 
 // -----------------------------------------------------------------------------
-// Part 2
+// Part 1
 // -----------------------------------------------------------------------------
-fn part_2(report: &Vec<&str>) -> crate::Result<i64> {
-    let (ones, zeros): (Vec<&str>, Vec<&str>) =
-        report.iter().partition(|curr| curr.as_bytes()[0] == b'1');
-    let (mut most_common, mut least_common) = if zeros.len() > ones.len() {
-        (zeros, ones)
-    } else {
-        (ones, zeros)
-    };
-    let mut i = 1;
-    while most_common.len() > 1 {
-        let (ones, zeros): (Vec<&str>, Vec<&str>) = most_common
-            .iter()
-            .partition(|curr| curr.as_bytes()[i] == b'1');
-        most_common = if zeros.len() > ones.len() {
-            zeros
+// Gamma and epsilon rates
+pub(crate) fn power_rates(report: &Report) -> (u128, u128) {
+    // Bit k of the count for column j is bit j of counts[k]
+    let mut counts: Vec<u128> = vec![];
+    report.values.iter().for_each(|&value| {
+        let mut carry = value;
+        for count in counts.iter_mut() {
+            let sum = *count ^ carry;
+            carry &= *count;
+            *count = sum;
+            if carry == 0 {
+                break;
+            }
+        }
+        if carry != 0 {
+            counts.push(carry);
+        }
+    });
+
+    // Columns where the count is greater than half of the lines
+    let half = report.values.len() / 2;
+    let mut greater = 0;
+    let mut equal = u128::MAX;
+    counts.iter().enumerate().rev().for_each(|(k, &count)| {
+        if half >> k & 1 == 0 {
+            greater |= equal & count;
+            equal &= !count;
         } else {
-            ones
-        };
-        i += 1;
+            equal &= count;
+        }
+    });
+    // Counts can be shorter than half, then higher bits of half are not matched
+    if half >> counts.len() != 0 {
+        greater = 0;
     }
-    i = 1;
-    while least_common.len() > 1 {
-        let (ones, zeros): (Vec<&str>, Vec<&str>) = least_common
-            .iter()
-            .partition(|curr| curr.as_bytes()[i] == b'1');
-        least_common = if ones.len() < zeros.len() || zeros.len() == 0 {
+    (greater, !greater & report.mask())
+}
+
+fn part_1(report: &Report) -> crate::Result<i64> {
+    let (gamma, epsilon) = power_rates(report);
+    product(gamma, epsilon)
+}
+
+// -----------------------------------------------------------------------------
+// Part 2
+// -----------------------------------------------------------------------------
+// Filter a sorted report down to one value, keeping the ones when `keep_ones`
+// says so for the given number of ones and zeros
+fn filter(sorted: &[u128], width: usize, keep_ones: impl Fn(usize, usize) -> bool) -> u128 {
+    let mut range = sorted;
+    let mut bit = width;
+    while range.len() > 1 && bit > 0 {
+        bit -= 1;
+        let split = range.partition_point(|value| value >> bit & 1 == 0);
+        let (zeros, ones) = range.split_at(split);
+        range = if keep_ones(ones.len(), zeros.len()) {
             ones
         } else {
             zeros
         };
-        i += 1;
     }
-    let most_common = i64::from_str_radix(most_common[0], 2)?;
-    let least_common = i64::from_str_radix(least_common[0], 2)?;
-    Ok(most_common * least_common)
+    range[0]
+}
+
+// Oxygen generator and CO2 scrubber ratings
+pub(crate) fn life_support_ratings(report: &Report) -> (u128, u128) {
+    let mut sorted = report.values.clone();
+    sorted.sort_unstable();
+    let oxygen = filter(&sorted, report.width, |ones, zeros| ones >= zeros);
+    let co2 = filter(&sorted, report.width, |ones, zeros| {
+        ones < zeros && ones > 0 || zeros == 0
+    });
+    (oxygen, co2)
+}
+
+fn part_2(report: &Report) -> crate::Result<i64> {
+    let (oxygen, co2) = life_support_ratings(report);
+    product(oxygen, co2)
 }
 
 // -----------------------------------------------------------------------------
@@ -89,9 +166,9 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Setup
     // -------------------------------------------------------------------------
-    // Read to vector
+    // Read to words
     let start_setup = Instant::now();
-    let report: Vec<&str> = buffer.lines().collect();
+    let report: Report = buffer.parse()?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -114,8 +191,8 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // Return
     // -------------------------------------------------------------------------
     Ok(RunData::new(
-        product_1 as i64,
-        product_2 as i64,
+        product_1,
+        product_2,
         Timing::new(
             time_setup,
             time_part_1,
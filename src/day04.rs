@@ -2,158 +2,383 @@
 //! I encoded the board into row and column indices for each possible ball.
 //! I took a huge performance penalty for using `method(self)` instead of
 //! `method(&self)` at first.
+//! The board size comes from the input, and the lines that win are chosen
+//! with `Rules`. The drawn numbers are indexed in draw order, and each index
+//! lists the board cells holding that number, so the tables grow with the
+//! input rather than with the largest number. Each ball only updates the
+//! counts of the lines it is on, so only those lines need to be checked for a
+//! win. A single pass over the balls ranks every board by when it wins, so the
+//! two parts are just the first and last boards in the ranking.
 
+use crate::cli::Options;
 use crate::prelude::*;
-use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 
-const NUMBER_ENTRIES: usize = 100;
-const NUMBER_SQ_ROOT: usize = 5;
+pub(crate) const OPTIONS: &[&str] = &["wins", "ranking"];
+
+// Lines through an entry
+const MAIN_DIAGONAL: u8 = 1;
+const ANTI_DIAGONAL: u8 = 2;
+const CORNER: u8 = 4;
+
+// -----------------------------------------------------------------------------
+// Rules data struct
+// -----------------------------------------------------------------------------
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Rules {
+    pub(crate) rows: bool,
+    pub(crate) columns: bool,
+    pub(crate) diagonals: bool,
+    pub(crate) corners: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            rows: true,
+            columns: true,
+            diagonals: false,
+            corners: false,
+        }
+    }
+}
+
+impl std::str::FromStr for Rules {
+    type Err = crate::Error;
+    // Comma separated list, such as `rows,columns,diagonals`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = Self {
+            rows: false,
+            columns: false,
+            diagonals: false,
+            corners: false,
+        };
+        for rule in s.split(',') {
+            match rule.trim() {
+                "rows" => rules.rows = true,
+                "columns" => rules.columns = true,
+                "diagonals" => rules.diagonals = true,
+                "corners" => rules.corners = true,
+                other => {
+                    return Err(crate::Error {
+                        message: format!(
+                            "unknown win condition '{}', expected {}",
+                            other, "rows, columns, diagonals or corners"
+                        ),
+                    })
+                }
+            }
+        }
+        Ok(rules)
+    }
+}
 
 // -----------------------------------------------------------------------------
-// Direction data struct
+// Board data struct
 // -----------------------------------------------------------------------------
+#[derive(Clone, Debug)]
 struct Board {
-    entry_rows: [usize; NUMBER_ENTRIES],
-    entry_columns: [usize; NUMBER_ENTRIES],
-    entry_diagonals: [usize; NUMBER_ENTRIES],
-    visited: [bool; NUMBER_ENTRIES],
-    // count at index 0 is a dummy value to make behavior same for all balls
-    visited_rows: [usize; NUMBER_SQ_ROOT + 1],
-    visited_columns: [usize; NUMBER_SQ_ROOT + 1],
-    visited_diagonals: [usize; 3],
+    size: usize,
+    // Number and lines through each cell, row by row
+    numbers: Vec<usize>,
+    cell_lines: Vec<u8>,
+    visited: Vec<bool>,
+    visited_rows: Vec<usize>,
+    visited_columns: Vec<usize>,
+    visited_diagonals: [usize; 2],
+    visited_corners: usize,
     won: bool,
 }
 
 impl Board {
-    fn new(board: itertools::Chunk<std::iter::Skip<std::str::Split<&str>>>) -> crate::Result<Self> {
-        let mut entry_rows = [0; NUMBER_ENTRIES];
-        let mut entry_columns = [0; NUMBER_ENTRIES];
-        let mut entry_diagonals = [0; NUMBER_ENTRIES];
-        board.into_iter().enumerate().for_each(|(i, line)| {
-            line.split_whitespace()
-                .into_iter()
-                .enumerate()
-                .for_each(|(j, number)| {
-                    let index: usize = number.trim().parse().expect("failed to parse number");
-                    let diagonal = if i == j {
-                        1
-                    } else if i == NUMBER_SQ_ROOT - j + 1 {
-                        2
-                    } else {
-                        0
-                    };
-                    entry_rows[index] = i + 1;
-                    entry_columns[index] = j + 1;
-                    entry_diagonals[index] = diagonal;
-                });
+    fn new(rows: &[Vec<usize>]) -> Self {
+        let size = rows.len();
+        let mut cell_lines = vec![0; size * size];
+        (0..size).for_each(|i| {
+            (0..size).for_each(|j| {
+                let lines = &mut cell_lines[i * size + j];
+                if i == j {
+                    *lines |= MAIN_DIAGONAL;
+                }
+                if i + j == size - 1 {
+                    *lines |= ANTI_DIAGONAL;
+                }
+                if (i == 0 || i == size - 1) && (j == 0 || j == size - 1) {
+                    *lines |= CORNER;
+                }
+            });
         });
-        Ok(Self {
-            entry_rows: entry_rows,
-            entry_columns: entry_columns,
-            entry_diagonals: entry_diagonals,
-            visited: [false; NUMBER_ENTRIES],
-            visited_rows: [0; NUMBER_SQ_ROOT + 1],
-            visited_columns: [0; NUMBER_SQ_ROOT + 1],
-            visited_diagonals: [0; 3],
+        Self {
+            size,
+            numbers: rows.iter().flatten().copied().collect(),
+            cell_lines,
+            visited: vec![false; size * size],
+            visited_rows: vec![0; size],
+            visited_columns: vec![0; size],
+            visited_diagonals: [0; 2],
+            visited_corners: 0,
             won: false,
-        })
+        }
     }
 
-    fn is_winner(&mut self) -> crate::Result<bool> {
+    // Mark a cell, returning whether the board has won
+    fn mark(&mut self, cell: usize, rules: &Rules) -> bool {
+        if self.visited[cell] {
+            return self.won;
+        }
+        self.visited[cell] = true;
+        let row = cell / self.size;
+        let column = cell % self.size;
+        let lines = self.cell_lines[cell];
+        self.visited_rows[row] += 1;
+        self.visited_columns[column] += 1;
+        self.visited_diagonals[0] += (lines & MAIN_DIAGONAL != 0) as usize;
+        self.visited_diagonals[1] += (lines & ANTI_DIAGONAL != 0) as usize;
+        self.visited_corners += (lines & CORNER != 0) as usize;
+        let number_corners = if self.size == 1 { 1 } else { 4 };
         self.won = self.won
-            || self
-                .visited_rows
-                .iter()
-                .skip(1)
-                .any(|visits| *visits == NUMBER_SQ_ROOT)
-            || self
-                .visited_columns
-                .iter()
-                .skip(1)
-                .any(|visits| *visits == NUMBER_SQ_ROOT)
-            || self
-                .visited_diagonals
-                .iter()
-                .skip(1)
-                .any(|visits| *visits == NUMBER_SQ_ROOT);
-        Ok(self.won)
+            || rules.rows && self.visited_rows[row] == self.size
+            || rules.columns && self.visited_columns[column] == self.size
+            || rules.diagonals
+                && (lines & MAIN_DIAGONAL != 0 && self.visited_diagonals[0] == self.size
+                    || lines & ANTI_DIAGONAL != 0 && self.visited_diagonals[1] == self.size)
+            || rules.corners && lines & CORNER != 0 && self.visited_corners == number_corners;
+        self.won
+    }
+
+    fn is_winner(&self) -> bool {
+        self.won
     }
 
-    fn score(&self) -> crate::Result<u32> {
-        Ok(self
-            .visited
+    // Sum of the unmarked numbers, None when it overflows
+    fn score(&self) -> Option<u64> {
+        self.numbers
             .iter()
-            .enumerate()
-            .fold(0, |score, (i, visited)| {
-                score
-                    + if !*visited && self.entry_rows[i] != 0 {
-                        i as u32
-                    } else {
-                        0
-                    }
-            }))
+            .zip(self.visited.iter())
+            .filter(|(_, visited)| !**visited)
+            .try_fold(0_u64, |score, (&number, _)| {
+                score.checked_add(number as u64)
+            })
     }
 }
 
 // -----------------------------------------------------------------------------
-// Part 1
+// Bingo data struct
 // -----------------------------------------------------------------------------
-fn part_1(balls: &Vec<usize>, boards: &mut Vec<Board>) -> crate::Result<(u32, usize)> {
-    let mut is_winner = false;
-    let mut winner = 0;
-    let mut ball = 0;
-    let mut balls_iter = balls.iter();
-    while !is_winner {
-        ball = *balls_iter.next().expect("insufficent balls");
-        boards.iter_mut().enumerate().for_each(|(i, board)| {
-            board.visited[ball] = true;
-            board.visited_rows[board.entry_rows[ball]] += 1;
-            board.visited_columns[board.entry_columns[ball]] += 1;
-            board.visited_diagonals[board.entry_diagonals[ball]] += 1;
-            if !is_winner {
-                is_winner = board.is_winner().expect("failed to check status");
-                winner = i;
+#[derive(Clone, Debug)]
+pub(crate) struct Bingo {
+    balls: Vec<usize>,
+    // Index of each ball in draw order, with repeated balls sharing an index
+    draws: Vec<usize>,
+    // Board and cell of every entry holding the ball of each index
+    entries: Vec<Vec<(usize, usize)>>,
+    boards: Vec<Board>,
+}
+
+impl std::str::FromStr for Bingo {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(|line| line.trim());
+        let balls: Vec<usize> = lines
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .map(|ball| ball.trim().parse())
+            .collect::<Result<_, _>>()?;
+
+        // Boards are separated by blank lines
+        let mut grids: Vec<Vec<Vec<usize>>> = vec![];
+        let mut grid = vec![];
+        for line in lines.chain(std::iter::once("")) {
+            if line.is_empty() {
+                if !grid.is_empty() {
+                    grids.push(std::mem::take(&mut grid));
+                }
+                continue;
             }
-        });
+            let row: Vec<usize> = line
+                .split_whitespace()
+                .map(|number| number.parse())
+                .collect::<Result<_, _>>()?;
+            grid.push(row);
+        }
+        let size = grids.first().map_or(0, |grid| grid.len());
+        if size == 0 {
+            return Err(crate::Error {
+                message: "no boards found".to_string(),
+            });
+        }
+        for (i, grid) in grids.iter().enumerate() {
+            if grid.len() != size || grid.iter().any(|row| row.len() != size) {
+                return Err(crate::Error {
+                    message: format!("board {} is not {} by {}", i + 1, size, size),
+                });
+            }
+        }
+
+        // Dense index of each ball, in the order they are first drawn
+        let mut indices: HashMap<usize, usize> = HashMap::with_capacity(balls.len());
+        let draws: Vec<usize> = balls
+            .iter()
+            .map(|&ball| {
+                let next = indices.len();
+                *indices.entry(ball).or_insert(next)
+            })
+            .collect();
+        let mut entries = vec![vec![]; indices.len()];
+        let mut seen = HashSet::with_capacity(size * size);
+        for (i, grid) in grids.iter().enumerate() {
+            seen.clear();
+            for (cell, &number) in grid.iter().flatten().enumerate() {
+                if !seen.insert(number) {
+                    return Err(crate::Error {
+                        message: format!("board {} has {} more than once", i + 1, number),
+                    });
+                }
+                if let Some(&index) = indices.get(&number) {
+                    entries[index].push((i, cell));
+                }
+            }
+        }
+        let boards = grids.iter().map(|grid| Board::new(grid)).collect();
+        Ok(Self {
+            balls,
+            draws,
+            entries,
+            boards,
+        })
     }
-    let score = boards[winner].score()?;
-    Ok((score, ball))
 }
 
 // -----------------------------------------------------------------------------
-// Part 2
+// Win order
 // -----------------------------------------------------------------------------
-fn part_2(
-    last_ball: usize,
-    balls: &Vec<usize>,
-    boards: &mut Vec<Board>,
-) -> crate::Result<(u32, usize)> {
-    let number_boards = boards.len();
-    let mut winner_count = 1;
-    let mut winner = 0;
-    let mut ball = 0;
-    let ball_index = balls
-        .iter()
-        .position(|ball| *ball == last_ball)
-        .expect("failed to find ball");
-    let mut balls_iter = balls.iter().skip(ball_index + 1);
-    while winner_count != number_boards {
-        ball = *balls_iter.next().expect("insufficent balls");
-        boards.iter_mut().enumerate().for_each(|(i, board)| {
-            if !board.is_winner().expect("failed to check status") {
-                board.visited[ball] = true;
-                board.visited_rows[board.entry_rows[ball]] += 1;
-                board.visited_columns[board.entry_columns[ball]] += 1;
-                board.visited_diagonals[board.entry_diagonals[ball]] += 1;
-                if board.is_winner().expect("failed to check status") {
-                    winner_count += 1;
-                    winner = i;
-                }
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Win {
+    pub(crate) board: usize,
+    pub(crate) ball: usize,
+    pub(crate) turn: usize,
+    pub(crate) score: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Ranking {
+    pub(crate) wins: Vec<Win>,
+    pub(crate) never_won: Vec<usize>,
+}
+
+// Play every ball, recording each board as it wins
+pub(crate) fn rank(bingo: &Bingo, rules: &Rules) -> crate::Result<Ranking> {
+    let mut boards = bingo.boards.clone();
+    let mut wins = Vec::with_capacity(boards.len());
+    for (turn, (&ball, &index)) in bingo.balls.iter().zip(bingo.draws.iter()).enumerate() {
+        for &(i, cell) in bingo.entries[index].iter() {
+            let board = &mut boards[i];
+            if !board.is_winner() && board.mark(cell, rules) {
+                let score = board
+                    .score()
+                    .and_then(|score| score.checked_mul(ball as u64))
+                    .ok_or_else(|| crate::Error {
+                        message: format!("score of board {} overflows", i + 1),
+                    })?;
+                wins.push(Win {
+                    board: i,
+                    ball,
+                    turn: turn + 1,
+                    score,
+                });
             }
+        }
+        if wins.len() == boards.len() {
+            break;
+        }
+    }
+    let never_won = boards
+        .iter()
+        .enumerate()
+        .filter(|(_, board)| !board.is_winner())
+        .map(|(i, _)| i)
+        .collect();
+    Ok(Ranking { wins, never_won })
+}
+
+// -----------------------------------------------------------------------------
+// Part 1
+// -----------------------------------------------------------------------------
+fn part_1(ranking: &Ranking) -> crate::Result<u64> {
+    Ok(ranking
+        .wins
+        .first()
+        .ok_or(crate::Error {
+            message: "no board wins".to_string(),
+        })?
+        .score)
+}
+
+// -----------------------------------------------------------------------------
+// Part 2
+// -----------------------------------------------------------------------------
+// The last board to win is only known once every board has won
+fn part_2(ranking: &Ranking) -> crate::Result<u64> {
+    if let Some(board) = ranking.never_won.first() {
+        return Err(crate::Error {
+            message: format!("board {} never wins, so no board wins last", board + 1),
         });
     }
-    let score = boards[winner].score()?;
-    Ok((score, ball))
+    Ok(ranking
+        .wins
+        .last()
+        .ok_or(crate::Error {
+            message: "no board wins".to_string(),
+        })?
+        .score)
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    let wins = options.parse::<Rules>("wins")?;
+    if wins.is_none() && !options.flag("ranking") {
+        return Ok(());
+    }
+    let rules = wins.unwrap_or_default();
+    let ranking = rank(&buffer.parse()?, &rules)?;
+    if wins.is_some() {
+        println!("    {}:", "Rules".purple().bold());
+        println!("      🦑 First score: {}", part_1(&ranking)?);
+        match part_2(&ranking) {
+            Ok(score) => println!("      🦑 Last score: {}", score),
+            Err(error) => println!("      🦑 Last score: none, {}", error.message),
+        }
+    }
+    if options.flag("ranking") {
+        println!("    {}:", "Ranking".purple().bold());
+        println!(
+            "      {:>5} {:>6} {:>5} {:>5} {:>8}",
+            "Place", "Board", "Ball", "Turn", "Score"
+        );
+        ranking.wins.iter().enumerate().for_each(|(place, win)| {
+            println!(
+                "      {:>5} {:>6} {:>5} {:>5} {:>8}",
+                place + 1,
+                win.board + 1,
+                win.ball,
+                win.turn,
+                win.score
+            )
+        });
+    }
+    if !ranking.never_won.is_empty() {
+        let boards: Vec<String> = ranking
+            .never_won
+            .iter()
+            .map(|board| (board + 1).to_string())
+            .collect();
+        println!("      Never won: board {}", boards.join(", "));
+    }
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -165,29 +390,16 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Read to vector
     let start_setup = Instant::now();
-    let balls: Vec<usize> = buffer
-        .lines()
-        .nth(0)
-        .expect("failed to parse line")
-        .split(",")
-        .map(|n| n.parse().expect("failed to parse ball"))
-        .collect();
-    let mut boards: Vec<Board> = buffer
-        .split("\n")
-        .skip(2)
-        .chunks(6)
-        .into_iter()
-        .map(|chunk| Board::new(chunk).expect("failed to parse board"))
-        .collect();
+    let bingo: Bingo = buffer.parse()?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
     // Part 1
     // -------------------------------------------------------------------------
-    // Score first winning board
+    // Rank all boards, then score first winning board
     let start_part_1 = Instant::now();
-    let (score_1, ball_1) = part_1(&balls, &mut boards)?;
-    let product_1 = score_1 * ball_1 as u32;
+    let ranking = rank(&bingo, &Rules::default())?;
+    let product_1 = part_1(&ranking)?;
     let time_part_1 = start_part_1.elapsed();
 
     // -------------------------------------------------------------------------
@@ -195,16 +407,19 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Score last winnig board
     let start_part_2 = Instant::now();
-    let (score_2, ball_2) = part_2(ball_1, &balls, &mut boards)?;
-    let product_2 = score_2 * ball_2 as u32;
+    let product_2 = part_2(&ranking)?;
     let time_part_2 = start_part_2.elapsed();
 
     // -------------------------------------------------------------------------
     // Return
     // -------------------------------------------------------------------------
     Ok(RunData::new(
-        product_1 as i64,
-        product_2 as i64,
+        i64::try_from(product_1).map_err(|_| crate::Error {
+            message: format!("first winning score {} overflows", product_1),
+        })?,
+        i64::try_from(product_2).map_err(|_| crate::Error {
+            message: format!("last winning score {} overflows", product_2),
+        })?,
         Timing::new(
             time_setup,
             time_part_1,
@@ -5,67 +5,501 @@
 //! was zero. Second, this difference reduced greatly when I switched to a
 //! narrower data type, using `i/u16` instead of `i/u32` and using a `u8` for
 //! my `grid`s representing the vents.
+//! The grid is sized from the bounding box of the segments, and the `u8`
+//! counts saturate so a point never counts as a new overlap twice. When the
+//! bounding box is too large for a dense grid, the overlaps come from the
+//! segments themselves instead. Segments on the same line overlap where a
+//! sweep over their endpoints is two deep, and segments on different lines
+//! cross in at most one point, so long segments cost no more than short ones.
+//! The vent density can also be drawn as a heatmap, binned down to a fixed
+//! size, with the axis aligned and diagonal vents in separate colors.
 
+use crate::cli::Options;
 use crate::prelude::*;
+use std::collections::{HashMap, HashSet};
 
-const GRID_SIZE: usize = 999;
+pub(crate) const OPTIONS: &[&str] = &["heatmap", "pgm", "ppm", "scale"];
+
+// Largest dense grid, in cells
+const DENSE_LIMIT: usize = 1 << 24;
+// Largest coordinate
+const MAX_COORDINATE: usize = u32::MAX as usize;
+// Heatmap sizes, in bins
+const IMAGE_SIZE: usize = 1_000;
+const NUMBER_COLUMNS: usize = 72;
+const DEFAULT_SCALE: usize = 1;
 
 // -----------------------------------------------------------------------------
 // Segment data struct
 // -----------------------------------------------------------------------------
-#[derive(Default, Debug)]
-struct Segment {
-    start: usize,
-    stop: usize,
-    step: usize,
-    diagonal: bool,
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Segment {
+    pub(crate) start: (usize, usize),
+    pub(crate) stop: (usize, usize),
 }
 
 impl std::str::FromStr for Segment {
     type Err = crate::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut line = s.splitn(7, |c| !char::is_numeric(c));
-        let start_x: usize = line.next().expect("failed to parse segment").parse()?;
-        let start_y: usize = line.next().expect("failed to parse segment").parse()?;
-        line.next();
-        line.next();
-        line.next();
-        let stop_x: usize = line.next().expect("failed to parse segment").parse()?;
-        let stop_y: usize = line.next().expect("failed to parse segment").parse()?;
-
-        let dx = (stop_x as i16 - start_x as i16).signum() as i8;
-        let dy = (stop_y as i16 - start_y as i16).signum() as i8;
-
-        let start = start_x * GRID_SIZE + start_y;
-        let stop = stop_x * GRID_SIZE + stop_y;
-        let step = (dx as i32 * GRID_SIZE as i32 + dy as i32).abs() as usize;
-
-        Ok(Self {
-            start: if start < stop { start } else { stop },
-            stop: if start < stop { stop } else { start },
-            step: step,
-            diagonal: dx != 0 && dy != 0,
-        })
+        let invalid = || crate::Error {
+            message: format!("invalid segment '{}', expected 'x1,y1 -> x2,y2'", s),
+        };
+        let point = |text: &str| -> crate::Result<(usize, usize)> {
+            let (x, y) = text.trim().split_once(',').ok_or_else(invalid)?;
+            let (x, y) = (x.trim().parse()?, y.trim().parse()?);
+            if x > MAX_COORDINATE || y > MAX_COORDINATE {
+                return Err(crate::Error {
+                    message: format!("coordinates above {} are not supported", MAX_COORDINATE),
+                });
+            }
+            Ok((x, y))
+        };
+        let (start, stop) = s.split_once("->").ok_or_else(invalid)?;
+        let segment = Self {
+            start: point(start)?,
+            stop: point(stop)?,
+        };
+        let (dx, dy) = segment.deltas();
+        if dx != 0 && dy != 0 && dx.abs() != dy.abs() {
+            return Err(crate::Error {
+                message: format!("segment '{}' is neither axis aligned nor diagonal", s),
+            });
+        }
+        Ok(segment)
     }
 }
 
+impl Segment {
+    fn deltas(&self) -> (isize, isize) {
+        (
+            self.stop.0 as isize - self.start.0 as isize,
+            self.stop.1 as isize - self.start.1 as isize,
+        )
+    }
+
+    pub(crate) fn is_diagonal(&self) -> bool {
+        let (dx, dy) = self.deltas();
+        dx != 0 && dy != 0
+    }
+
+    // Number of steps from start to stop
+    fn length(&self) -> usize {
+        let (dx, dy) = self.deltas();
+        std::cmp::max(dx.unsigned_abs(), dy.unsigned_abs())
+    }
+
+    // Start, unit step and number of steps, always stepping towards +x, or
+    // towards +y when vertical, so segments on one line step the same way
+    fn run(&self) -> ((i64, i64), (i64, i64), i64) {
+        let (dx, dy) = self.deltas();
+        let (start, stop) = if dx < 0 || dx == 0 && dy < 0 {
+            (self.stop, self.start)
+        } else {
+            (self.start, self.stop)
+        };
+        let step = match (dx.signum().abs(), dy.signum() * dx.signum()) {
+            (0, 0) if dy == 0 => (1, 0),
+            (0, _) => (0, 1),
+            (_, sign) => (1, sign as i64),
+        };
+        (
+            (start.0 as i64, start.1 as i64),
+            step,
+            std::cmp::max(
+                stop.0 as i64 - start.0 as i64,
+                stop.1 as i64 - start.1 as i64,
+            ),
+        )
+    }
+}
+
+pub(crate) fn parse_segments(buffer: &str) -> crate::Result<Vec<Segment>> {
+    buffer
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            line.parse().map_err(|error: crate::Error| crate::Error {
+                message: format!("line {}: {}", i + 1, error.message),
+            })
+        })
+        .collect()
+}
+
 // -----------------------------------------------------------------------------
-// Part 1/2
+// Overlaps without a grid
 // -----------------------------------------------------------------------------
-fn part_1(grid: &mut [u8; GRID_SIZE * GRID_SIZE], segments: &Vec<Segment>) -> crate::Result<u16> {
-    let mut count = 0;
-    segments.iter().for_each(|segment| {
-        (segment.start..=segment.stop)
-            .step_by(segment.step)
-            .for_each(|index| unsafe {
-                let point = grid.get_unchecked_mut(index);
-                *point += 1;
-                if *point == 2 {
-                    count += 1;
+// Line through a point with a given step, as the step and the coordinate that
+// stays constant along it, with the position of the point along the line
+fn line(point: (i64, i64), step: (i64, i64)) -> (((i64, i64), i64), i64) {
+    match step {
+        (1, 0) => ((step, point.1), point.0),
+        (0, 1) => ((step, point.0), point.1),
+        (1, 1) => ((step, point.1 - point.0), point.0),
+        _ => ((step, point.1 + point.0), point.0),
+    }
+}
+
+// Parts of a line covered by at least two of the intervals
+fn covered_twice(intervals: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let mut events: Vec<(i64, i64)> = intervals
+        .iter()
+        .flat_map(|&(low, high)| [(low, 1), (high + 1, -1)])
+        .collect();
+    events.sort_unstable();
+    let (mut depth, mut start) = (0, 0);
+    let mut covered = vec![];
+    events.iter().for_each(|&(position, change)| {
+        if depth < 2 && depth + change >= 2 {
+            start = position;
+        } else if depth >= 2 && depth + change < 2 {
+            covered.push((start, position - 1));
+        }
+        depth += change;
+    });
+    covered
+}
+
+// Single lattice point where two segments on different lines cross
+fn crossing(
+    a: ((i64, i64), (i64, i64), i64),
+    b: ((i64, i64), (i64, i64), i64),
+) -> Option<(i64, i64)> {
+    let (((xa, ya), (dxa, dya), length_a), ((xb, yb), (dxb, dyb), length_b)) = (a, b);
+    let determinant = dxb * dya - dxa * dyb;
+    if determinant == 0 {
+        return None;
+    }
+    let (ex, ey) = (xb - xa, yb - ya);
+    let (t, s) = (dxb * ey - dyb * ex, dxa * ey - dya * ex);
+    if t % determinant != 0 || s % determinant != 0 {
+        return None;
+    }
+    let (t, s) = (t / determinant, s / determinant);
+    if (0..=length_a).contains(&t) && (0..=length_b).contains(&s) {
+        Some((xa + t * dxa, ya + t * dya))
+    } else {
+        None
+    }
+}
+
+// Number of points covered by at least two segments
+fn count_overlaps(segments: &[Segment]) -> usize {
+    let runs: Vec<_> = segments.iter().map(|segment| segment.run()).collect();
+
+    // Overlaps between segments on the same line
+    let mut lines: HashMap<_, Vec<(i64, i64)>> = HashMap::new();
+    runs.iter().for_each(|&(start, step, length)| {
+        let (key, position) = line(start, step);
+        lines
+            .entry(key)
+            .or_default()
+            .push((position, position + length));
+    });
+    let overlaps: HashMap<_, Vec<(i64, i64)>> = lines
+        .iter()
+        .map(|(key, intervals)| (*key, covered_twice(intervals)))
+        .filter(|(_, covered)| !covered.is_empty())
+        .collect();
+    let mut count = overlaps
+        .values()
+        .flatten()
+        .map(|(low, high)| (high - low + 1) as usize)
+        .sum::<usize>();
+
+    // Crossings between lines, where a point on the overlaps of several lines
+    // was counted once for each of them
+    let crossings: HashSet<(i64, i64)> = runs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &a)| runs[i + 1..].iter().filter_map(move |&b| crossing(a, b)))
+        .collect();
+    crossings.iter().for_each(|&point| {
+        let lines_covered = [(1, 0), (0, 1), (1, 1), (1, -1)]
+            .iter()
+            .filter(|&&step| {
+                let (key, position) = line(point, step);
+                match overlaps.get(&key) {
+                    Some(covered) => {
+                        let i = covered.partition_point(|&(low, _)| low <= position);
+                        i > 0 && covered[i - 1].1 >= position
+                    }
+                    None => false,
                 }
             })
+            .count();
+        match lines_covered {
+            0 => count += 1,
+            _ => count -= lines_covered - 1,
+        }
     });
-    Ok(count)
+    count
+}
+
+// -----------------------------------------------------------------------------
+// Vent floor enum
+// -----------------------------------------------------------------------------
+pub(crate) enum Floor {
+    Dense {
+        min: (usize, usize),
+        height: usize,
+        cells: Vec<u8>,
+    },
+    // Segments added so far, and the overlaps between them
+    Sparse {
+        segments: Vec<Segment>,
+        overlaps: usize,
+    },
+}
+
+impl Floor {
+    // Dense grid over the bounding box when it is small enough
+    pub(crate) fn new(segments: &[Segment]) -> Self {
+        let (mut min, mut max) = ((usize::MAX, usize::MAX), (0, 0));
+        segments
+            .iter()
+            .flat_map(|segment| [segment.start, segment.stop])
+            .for_each(|(x, y)| {
+                min = (min.0.min(x), min.1.min(y));
+                max = (max.0.max(x), max.1.max(y));
+            });
+        let sparse = Self::Sparse {
+            segments: vec![],
+            overlaps: 0,
+        };
+        if segments.is_empty() {
+            return sparse;
+        }
+        let (width, height) = (max.0 - min.0 + 1, max.1 - min.1 + 1);
+        match width.checked_mul(height) {
+            Some(size) if size <= DENSE_LIMIT => Self::Dense {
+                min,
+                height,
+                cells: vec![0; size],
+            },
+            _ => sparse,
+        }
+    }
+
+    // Add the segments, returning the number of new overlapping points
+    pub(crate) fn add(&mut self, segments: &[Segment]) -> usize {
+        let mut count = 0;
+        match self {
+            Self::Dense { min, height, cells } => segments.iter().for_each(|segment| {
+                let (dx, dy) = segment.deltas();
+                let step = dx.signum() * *height as isize + dy.signum();
+                let mut index = (segment.start.0 - min.0) * *height + segment.start.1 - min.1;
+                (0..segment.length() + 1).for_each(|_| {
+                    let point = &mut cells[index];
+                    *point = point.saturating_add(1);
+                    count += (*point == 2) as usize;
+                    index = index.wrapping_add(step as usize);
+                });
+            }),
+            Self::Sparse {
+                segments: added,
+                overlaps,
+            } => {
+                added.extend_from_slice(segments);
+                let total = count_overlaps(added);
+                count = total - *overlaps;
+                *overlaps = total;
+            }
+        }
+        count
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Part 1/2
+// -----------------------------------------------------------------------------
+fn part_1(floor: &mut Floor, segments: &[Segment]) -> crate::Result<usize> {
+    Ok(floor.add(segments))
+}
+
+// -----------------------------------------------------------------------------
+// Heatmap data struct
+// -----------------------------------------------------------------------------
+// Steps along one axis before leaving `bin`, out of `bins` over `span`
+fn steps_in_bin(offset: u128, delta: isize, bin: u128, bins: u128, span: u128) -> u128 {
+    let first = |bin: u128| (bin * span).div_ceil(bins);
+    match delta.signum() {
+        1 => first(bin + 1) - offset,
+        -1 => offset - first(bin) + 1,
+        _ => u128::MAX,
+    }
+}
+
+fn step_by(offset: u128, delta: isize, steps: u128) -> u128 {
+    match delta.signum() {
+        1 => offset + steps,
+        -1 => offset.wrapping_sub(steps),
+        _ => offset,
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Heatmap {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    // Number of vent points in each bin, row by row
+    pub(crate) axis: Vec<u64>,
+    pub(crate) diagonal: Vec<u64>,
+}
+
+impl Heatmap {
+    // Bin the bounding box of the segments down to at most `max_width` by
+    // `max_height` bins
+    pub(crate) fn new(segments: &[Segment], max_width: usize, max_height: usize) -> Self {
+        let (mut min, mut max) = ((usize::MAX, usize::MAX), (0, 0));
+        segments
+            .iter()
+            .flat_map(|segment| [segment.start, segment.stop])
+            .for_each(|(x, y)| {
+                min = (min.0.min(x), min.1.min(y));
+                max = (max.0.max(x), max.1.max(y));
+            });
+        let span = (
+            max.0.saturating_sub(min.0) as u128 + 1,
+            max.1.saturating_sub(min.1) as u128 + 1,
+        );
+        let width = std::cmp::min(span.0, max_width as u128) as usize;
+        let height = std::cmp::min(span.1, max_height as u128) as usize;
+        let mut heatmap = Self {
+            width,
+            height,
+            axis: vec![0; width * height],
+            diagonal: vec![0; width * height],
+        };
+        segments.iter().for_each(|segment| {
+            let bins = if segment.is_diagonal() {
+                &mut heatmap.diagonal
+            } else {
+                &mut heatmap.axis
+            };
+            // Step a bin at a time, counting the points in each
+            let (dx, dy) = segment.deltas();
+            let mut offset = (
+                (segment.start.0 - min.0) as u128,
+                (segment.start.1 - min.1) as u128,
+            );
+            let mut remaining = segment.length() as u128 + 1;
+            while remaining > 0 {
+                let i = offset.0 * width as u128 / span.0;
+                let j = offset.1 * height as u128 / span.1;
+                let steps = std::cmp::min(
+                    steps_in_bin(offset.0, dx, i, width as u128, span.0),
+                    steps_in_bin(offset.1, dy, j, height as u128, span.1),
+                )
+                .min(remaining);
+                bins[i as usize + j as usize * width] += steps as u64;
+                remaining -= steps;
+                offset = (step_by(offset.0, dx, steps), step_by(offset.1, dy, steps));
+            }
+        });
+        heatmap
+    }
+
+    fn max(&self) -> u64 {
+        self.axis
+            .iter()
+            .zip(&self.diagonal)
+            .map(|(axis, diagonal)| axis + diagonal)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Gray levels of the total density
+    pub(crate) fn levels(&self) -> Vec<u8> {
+        let max = std::cmp::max(self.max(), 1) as u128;
+        self.axis
+            .iter()
+            .zip(&self.diagonal)
+            .map(|(axis, diagonal)| ((axis + diagonal) as u128 * 255 / max) as u8)
+            .collect()
+    }
+
+    // Axis aligned vents in blue, diagonal vents in red, and both in magenta
+    pub(crate) fn colors(&self) -> Vec<(u8, u8, u8)> {
+        let max = std::cmp::max(self.max(), 1) as u128;
+        let level = |count: u64| (count as u128 * 255 / max) as u8;
+        self.axis
+            .iter()
+            .zip(&self.diagonal)
+            .map(|(&axis, &diagonal)| {
+                (
+                    level(diagonal),
+                    level(std::cmp::min(axis, diagonal)) / 2,
+                    level(axis),
+                )
+            })
+            .collect()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    let pgm = options.value("pgm");
+    let ppm = options.value("ppm");
+    if !options.flag("heatmap") && pgm.is_none() && ppm.is_none() {
+        return Ok(());
+    }
+    let segments = parse_segments(buffer)?;
+    if options.flag("heatmap") {
+        // Terminal cells are about twice as tall as they are wide
+        let heatmap = Heatmap::new(&segments, NUMBER_COLUMNS, NUMBER_COLUMNS / 2);
+        println!("    {}:", "Heatmap".purple().bold());
+        let levels = heatmap.levels();
+        let colors = heatmap.colors();
+        levels
+            .chunks(heatmap.width)
+            .zip(colors.chunks(heatmap.width))
+            .for_each(|(levels, colors)| {
+                let row: String = levels
+                    .iter()
+                    .zip(colors)
+                    .map(|(&level, &(r, g, b))| {
+                        let shade = match level {
+                            0 => " ",
+                            1..=63 => "░",
+                            64..=127 => "▒",
+                            128..=191 => "▓",
+                            _ => "█",
+                        };
+                        // Keep faint bins visible
+                        let lift = |channel: u8| channel.saturating_add(64 * (channel > 0) as u8);
+                        shade.truecolor(lift(r), lift(g), lift(b)).to_string()
+                    })
+                    .collect();
+                println!("    {}", row);
+            });
+        println!("    {} axis aligned  {} diagonal", "█".blue(), "█".red());
+    }
+    if pgm.is_some() || ppm.is_some() {
+        let scale = options.parse("scale")?.unwrap_or(DEFAULT_SCALE);
+        let heatmap = Heatmap::new(&segments, IMAGE_SIZE, IMAGE_SIZE);
+        if let Some(path) = pgm {
+            output::write_pgm(
+                path,
+                heatmap.width,
+                heatmap.height,
+                scale,
+                &heatmap.levels(),
+            )?;
+        }
+        if let Some(path) = ppm {
+            output::write_ppm(
+                path,
+                heatmap.width,
+                heatmap.height,
+                scale,
+                &heatmap.colors(),
+            )?;
+        }
+    }
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -77,17 +511,10 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Read to vector
     let start_setup = Instant::now();
-    let mut segments_1: Vec<Segment> = Vec::new();
-    let mut segments_2: Vec<Segment> = Vec::new();
-    buffer.lines().for_each(|line| {
-        let segment: Segment = line.parse().expect("failed to parse line");
-        if segment.diagonal {
-            segments_2.push(segment)
-        } else {
-            segments_1.push(segment)
-        };
-    });
-    let mut grid = [0_u8; GRID_SIZE * GRID_SIZE];
+    let segments = parse_segments(&buffer)?;
+    let (segments_2, segments_1): (Vec<Segment>, Vec<Segment>) =
+        segments.iter().partition(|segment| segment.is_diagonal());
+    let mut floor = Floor::new(&segments);
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -95,7 +522,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Count intersections
     let start_part_1 = Instant::now();
-    let count_1 = part_1(&mut grid, &segments_1)?;
+    let count_1 = part_1(&mut floor, &segments_1)?;
     let time_part_1 = start_part_1.elapsed();
 
     // -------------------------------------------------------------------------
@@ -103,7 +530,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Count all intersections
     let start_part_2 = Instant::now();
-    let count_2 = count_1 + part_1(&mut grid, &segments_2)?;
+    let count_2 = count_1 + part_1(&mut floor, &segments_2)?;
     let time_part_2 = start_part_2.elapsed();
 
     // -------------------------------------------------------------------------
@@ -4,22 +4,91 @@
 //! Of note, manually rotating seems to be faster than `rotate_left(1)` in this
 //! particular problem. Also of note, there is no need to actually rotate all of
 //! the fish if you rotate the meaning of each index.
+//! For very long projections, the total population follows the recurrence
+//! `f(n) = f(n - 7) + f(n - 9)`, so `f(n)` is a combination of the first nine
+//! totals with the coefficients of `x^n mod (x^9 - x^2 - 1)`. Squaring that
+//! polynomial gets to any generation in a logarithmic number of steps. The
+//! arithmetic is exact in `u128`, falling back to a big integer on overflow.
+//! The timers and number of offspring are set by a `Lifecycle`, which changes
+//! the lags and multipliers in the recurrence.
 
+use crate::bignum::BigUint;
+use crate::cli::Options;
 use crate::prelude::*;
+use std::convert::TryFrom;
+
+pub(crate) const OPTIONS: &[&str] = &["generations", "reset", "newborn", "offspring", "series"];
 
-const NUMBER_DAYS: usize = 9;
 const NUMBER_GENERATIONS_SMALL: usize = 80;
 const NUMBER_GENERATIONS_BIG: usize = 256;
+// Largest population to compute exactly
+const MAX_DIGITS: f64 = 100_000.0;
+// Longest series to print, since a population that never grows never stops it
+const MAX_SERIES: usize = 100_000;
 
 // -----------------------------------------------------------------------------
-// Part 1
+// Lifecycle data struct
 // -----------------------------------------------------------------------------
-fn part_1(population: &mut [usize; NUMBER_DAYS], generations: usize) -> crate::Result<usize> {
-    (0..generations).for_each(|i| {
-        let fish_day_0 = population[i % NUMBER_DAYS];
-        let day_6 = (i + NUMBER_DAYS - 2) % NUMBER_DAYS;
-        population[day_6] += fish_day_0;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Lifecycle {
+    pub(crate) reset: usize,   // timer after spawning
+    pub(crate) newborn: usize, // timer of new fish
+    pub(crate) offspring: usize,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self {
+            reset: 6,
+            newborn: 8,
+            offspring: 1,
+        }
+    }
+}
+
+impl Lifecycle {
+    // Number of distinct timer values
+    pub(crate) fn states(&self) -> usize {
+        std::cmp::max(self.reset, self.newborn) + 1
+    }
+
+    // Population recurrence as (lag, multiplier) pairs
+    fn taps(&self) -> [(usize, u128); 2] {
+        [
+            (self.reset + 1, 1),
+            (self.newborn + 1, self.offspring as u128),
+        ]
+    }
+}
+
+// Fish counts by timer, with the meaning of each index rotating every
+// generation, so the fish with timer 0 at generation `i` are at `i % states`
+fn advance(population: &mut [usize], lifecycle: &Lifecycle, start: usize, stop: usize) {
+    let states = population.len();
+    // Step the indices instead of taking a remainder every generation
+    let wrap = |index: usize| if index + 1 == states { 0 } else { index + 1 };
+    let mut day_0 = start % states;
+    let mut reset = (start + lifecycle.reset + 1) % states;
+    let mut newborn = (start + lifecycle.newborn + 1) % states;
+    (start..stop).for_each(|_| {
+        let fish_day_0 = std::mem::take(&mut population[day_0]);
+        population[reset] += fish_day_0;
+        population[newborn] += lifecycle.offspring * fish_day_0;
+        day_0 = wrap(day_0);
+        reset = wrap(reset);
+        newborn = wrap(newborn);
     });
+}
+
+// -----------------------------------------------------------------------------
+// Part 1
+// -----------------------------------------------------------------------------
+fn part_1(
+    population: &mut [usize],
+    lifecycle: &Lifecycle,
+    generations: usize,
+) -> crate::Result<usize> {
+    advance(population, lifecycle, 0, generations);
     Ok(population.iter().sum())
 }
 
@@ -27,18 +96,306 @@ fn part_1(population: &mut [usize; NUMBER_DAYS], generations: usize) -> crate::R
 // Part 2
 // -----------------------------------------------------------------------------
 fn part_2(
-    population: &mut [usize; NUMBER_DAYS],
+    population: &mut [usize],
+    lifecycle: &Lifecycle,
     initial_generations: usize,
     final_generations: usize,
 ) -> crate::Result<usize> {
-    (initial_generations..final_generations).for_each(|i| {
-        let fish_day_0 = population[i % NUMBER_DAYS];
-        let day_6 = (i + NUMBER_DAYS - 2) % NUMBER_DAYS;
-        population[day_6] += fish_day_0;
-    });
+    advance(
+        population,
+        lifecycle,
+        initial_generations,
+        final_generations,
+    );
     Ok(population.iter().sum())
 }
 
+// -----------------------------------------------------------------------------
+// Count trait, for exact arithmetic in u128 or a big integer
+// -----------------------------------------------------------------------------
+pub(crate) trait Count: Clone {
+    fn from_u128(value: u128) -> Self;
+    fn try_add(&self, other: &Self) -> Option<Self>;
+    fn try_mul(&self, other: &Self) -> Option<Self>;
+}
+
+impl Count for u128 {
+    fn from_u128(value: u128) -> Self {
+        value
+    }
+
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        self.checked_add(*other)
+    }
+
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        self.checked_mul(*other)
+    }
+}
+
+impl Count for BigUint {
+    fn from_u128(value: u128) -> Self {
+        value.into()
+    }
+
+    fn try_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn try_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Projection
+// -----------------------------------------------------------------------------
+// Product of polynomials, reduced with the recurrence, such as
+// `x^k = x^(k - 7) + x^(k - 9)`
+fn multiply_reduce<C: Count>(a: &[C], b: &[C], lifecycle: &Lifecycle) -> Option<Vec<C>> {
+    let order = lifecycle.states();
+    let mut product = vec![C::from_u128(0); 2 * order - 1];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            product[i + j] = product[i + j].try_add(&a.try_mul(b)?)?;
+        }
+    }
+    let taps = lifecycle.taps();
+    for k in (order..2 * order - 1).rev() {
+        let coefficient = product[k].clone();
+        for (lag, multiplier) in taps.iter() {
+            let term = coefficient.try_mul(&C::from_u128(*multiplier))?;
+            product[k - lag] = product[k - lag].try_add(&term)?;
+        }
+    }
+    product.truncate(order);
+    Some(product)
+}
+
+// Total population after each generation, until the total outgrows u128
+fn totals(population: &[usize], lifecycle: &Lifecycle, generations: usize) -> Vec<u128> {
+    let states = population.len();
+    let mut population: Vec<u128> = population.iter().map(|&count| count as u128).collect();
+    let mut totals = vec![];
+    for i in 0..=generations {
+        match population
+            .iter()
+            .try_fold(0_u128, |total, &count| total.checked_add(count))
+        {
+            Some(total) => totals.push(total),
+            None => break,
+        }
+        let fish_day_0 = std::mem::take(&mut population[i % states]);
+        let newborns = (lifecycle.offspring as u128).saturating_mul(fish_day_0);
+        let reset = &mut population[(i + lifecycle.reset + 1) % states];
+        *reset = reset.saturating_add(fish_day_0);
+        let newborn = &mut population[(i + lifecycle.newborn + 1) % states];
+        *newborn = newborn.saturating_add(newborns);
+    }
+    totals
+}
+
+// Totals for printing, up to a limit on the number of generations
+pub(crate) fn series(
+    population: &[usize],
+    lifecycle: &Lifecycle,
+    generations: usize,
+) -> crate::Result<Vec<u128>> {
+    if generations > MAX_SERIES {
+        return Err(crate::Error {
+            message: format!(
+                "series of {} generations is longer than the limit of {}",
+                generations, MAX_SERIES
+            ),
+        });
+    }
+    Ok(totals(population, lifecycle, generations))
+}
+
+// Total population after any number of generations, if it fits the count type
+pub(crate) fn project<C: Count>(
+    population: &[usize],
+    lifecycle: &Lifecycle,
+    generations: u64,
+) -> Option<C> {
+    let order = lifecycle.states();
+    let totals = totals(population, lifecycle, order - 1);
+    if generations < order as u64 {
+        return totals
+            .get(generations as usize)
+            .map(|total| C::from_u128(*total));
+    }
+    // Coefficients of x^generations, by squaring
+    let unit = |power: usize| -> Vec<C> {
+        (0..order)
+            .map(|i| C::from_u128((i == power) as u128))
+            .collect()
+    };
+    let mut coefficients = unit(0);
+    let mut base = if order > 1 {
+        unit(1)
+    } else {
+        // x is already reduced to a constant
+        vec![C::from_u128(1 + lifecycle.offspring as u128)]
+    };
+    let mut remaining = generations;
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            coefficients = multiply_reduce(&coefficients, &base, lifecycle)?;
+        }
+        remaining >>= 1;
+        if remaining > 0 {
+            base = multiply_reduce(&base, &base, lifecycle)?;
+        }
+    }
+    if totals.len() < order {
+        return None;
+    }
+    coefficients
+        .iter()
+        .zip(totals.iter())
+        .try_fold(C::from_u128(0), |sum, (coefficient, total)| {
+            sum.try_add(&coefficient.try_mul(&C::from_u128(*total))?)
+        })
+}
+
+// Rough number of decimal digits in the population, from the growth rate
+fn estimate_digits(population: &[usize], lifecycle: &Lifecycle, generations: u64) -> f64 {
+    // Growth rate is the root of x^order = sum of multiplier * x^(order - lag),
+    // which is between 1 and 1 + offspring
+    let order = lifecycle.states() as i32;
+    let taps = lifecycle.taps();
+    let (mut low, mut high) = (1.0_f64, 1.0 + lifecycle.offspring as f64);
+    (0..64).for_each(|_| {
+        let middle = (low + high) / 2.0;
+        let spawned: f64 = taps
+            .iter()
+            .map(|(lag, multiplier)| *multiplier as f64 * middle.powi(order - *lag as i32))
+            .sum();
+        if middle.powi(order) < spawned {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    });
+    let total = population.iter().sum::<usize>().max(1) as f64;
+    total.log10() + generations as f64 * low.log10()
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Population {
+    Exact(u128),
+    Big(BigUint),
+}
+
+impl fmt::Display for Population {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Exact(count) => write!(f, "{}", count),
+            Self::Big(count) => write!(f, "{}", count),
+        }
+    }
+}
+
+// Exact population, checking the u128 result against the big integer one
+pub(crate) fn population(
+    population: &[usize],
+    lifecycle: &Lifecycle,
+    generations: u64,
+) -> crate::Result<Population> {
+    let digits = estimate_digits(population, lifecycle, generations);
+    if digits > MAX_DIGITS {
+        return Err(crate::Error {
+            message: format!(
+                "population after {} generations has about {:.0} digits, too many to compute",
+                generations, digits
+            ),
+        });
+    }
+    let big: BigUint =
+        project(population, lifecycle, generations).expect("big integers do not overflow");
+    match project::<u128>(population, lifecycle, generations) {
+        Some(count) if BigUint::from(count) == big => Ok(Population::Exact(count)),
+        Some(count) => Err(crate::Error {
+            message: format!("projections disagree, {} and {}", count, big),
+        }),
+        None => Ok(Population::Big(big)),
+    }
+}
+
+pub(crate) fn parse_population(buffer: &str, lifecycle: &Lifecycle) -> crate::Result<Vec<usize>> {
+    let mut population = vec![0; lifecycle.states()];
+    for age in buffer.trim().split(',') {
+        let age: usize = age.trim().parse()?;
+        if age >= population.len() {
+            return Err(crate::Error {
+                message: format!("invalid fish age {}", age),
+            });
+        }
+        population[age] += 1;
+    }
+    Ok(population)
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    if !OPTIONS.iter().any(|option| options.flag(option)) {
+        return Ok(());
+    }
+    let default = Lifecycle::default();
+    let lifecycle = Lifecycle {
+        reset: options.parse("reset")?.unwrap_or(default.reset),
+        newborn: options.parse("newborn")?.unwrap_or(default.newborn),
+        offspring: options.parse("offspring")?.unwrap_or(default.offspring),
+    };
+    let generations: Vec<u64> = match options.value("generations") {
+        Some(list) => list
+            .split(',')
+            .map(|generations| generations.trim().parse())
+            .collect::<Result<_, _>>()?,
+        None => vec![
+            NUMBER_GENERATIONS_SMALL as u64,
+            NUMBER_GENERATIONS_BIG as u64,
+        ],
+    };
+    let fish = parse_population(buffer, &lifecycle)?;
+    println!(
+        "    {}: reset {}, newborn {}, offspring {}",
+        "Lifecycle".purple().bold(),
+        lifecycle.reset,
+        lifecycle.newborn,
+        lifecycle.offspring
+    );
+    for &generation in generations.iter() {
+        let count = population(&fish, &lifecycle, generation)?.to_string();
+        println!(
+            "    {}:",
+            format!("Generation {}", generation).purple().bold()
+        );
+        println!("      🐟 Count: {}", count);
+        println!("      Digits: {}", count.len());
+    }
+    if options.flag("series") {
+        let last = generations.iter().max().copied().unwrap_or(0);
+        let last = usize::try_from(last).unwrap_or(usize::MAX);
+        let totals = series(&fish, &lifecycle, last)?;
+        println!("    {}:", "Series".purple().bold());
+        totals
+            .iter()
+            .enumerate()
+            .for_each(|(generation, total)| println!("      {:>5} {}", generation, total));
+        if totals.len() <= last {
+            println!(
+                "      Stopped at generation {}, the count outgrows u128",
+                totals.len()
+            );
+        }
+    }
+    Ok(())
+}
+
 // -----------------------------------------------------------------------------
 // Run
 // -----------------------------------------------------------------------------
@@ -48,10 +405,8 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Read to vector
     let start_setup = Instant::now();
-    let mut initial_population = [0; NUMBER_DAYS];
-    buffer.trim().split(',').for_each(|age| {
-        initial_population[age.parse::<usize>().expect("failed to parse fish")] += 1
-    });
+    let lifecycle = Lifecycle::default();
+    let mut initial_population = parse_population(&buffer, &lifecycle)?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -59,7 +414,11 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Count 80 days of fish
     let start_part_1 = Instant::now();
-    let count_1 = part_1(&mut initial_population, NUMBER_GENERATIONS_SMALL)?;
+    let count_1 = part_1(
+        &mut initial_population,
+        &lifecycle,
+        NUMBER_GENERATIONS_SMALL,
+    )?;
     let time_part_1 = start_part_1.elapsed();
 
     // -------------------------------------------------------------------------
@@ -69,6 +428,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     let start_part_2 = Instant::now();
     let count_2 = part_2(
         &mut initial_population,
+        &lifecycle,
         NUMBER_GENERATIONS_SMALL,
         NUMBER_GENERATIONS_BIG,
     )?;
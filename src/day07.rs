@@ -1,47 +1,279 @@
 //! Day 7:
-//! The triangle number formula helps tidy up the code here. I am nearly certain
-//! that the answer for part 2 has to occur near the average, but I don't have
-//! a full proof of this hunch. Sorting is the bottleneck, but using an
-//! unstable sort helps somewhat.
+//! The triangle number formula helps tidy up the code here. Each fuel model
+//! is a `FuelCost` for moving one crab a given distance. When that cost is
+//! convex in the distance, the total fuel is convex in the alignment position,
+//! as a sum of convex functions, so galloping out from a guess and then
+//! bisecting on the slope finds the exact optimum. This settles my old hunch
+//! about part 2 being near the average without needing a proof. Costs that
+//! are not convex fall back to trying every position in range. Costs are
+//! monotone, so the cost of the longest distance decides up front whether
+//! the totals fit in a `u64` or need a `u128`, and anything that still does
+//! not fit is an error rather than a wrapped answer.
 
+use crate::cli::Options;
 use crate::prelude::*;
+use std::convert::TryFrom;
+
+pub(crate) const OPTIONS: &[&str] = &["align", "power"];
 
 // -----------------------------------------------------------------------------
 // Gauss sum formula for 1 + 2 + 3 + ... + n = n * (n + 1) / 2
 // -----------------------------------------------------------------------------
-fn gauss_sum(n: u32) -> u32 {
+// Distances between u32 positions keep n * (n + 1) below 2^64
+fn gauss_sum(n: u64) -> u64 {
     n * (n + 1) / 2
 }
 
 // -----------------------------------------------------------------------------
-// Part 1
+// Fuel cost trait
+// -----------------------------------------------------------------------------
+pub(crate) trait FuelCost {
+    // Fuel to move one crab a given distance, if it fits in a u64
+    fn checked_cost(&self, distance: u64) -> Option<u64>;
+
+    // Fuel for a distance no longer than one that passed `checked_cost`, for
+    // the hot loops
+    fn cost(&self, distance: u64) -> u64 {
+        self.checked_cost(distance).unwrap_or(u64::MAX)
+    }
+
+    // Convex costs allow a binary search for the optimum
+    fn is_convex(&self) -> bool {
+        false
+    }
+
+    // Starting guess for the search, the average suits costs that grow
+    // quadratically
+    fn guess(&self, positions: &[u32]) -> u32 {
+        (positions.iter().map(|&p| p as u64).sum::<u64>() / positions.len() as u64) as u32
+    }
+}
+
+pub(crate) struct Linear;
+
+impl FuelCost for Linear {
+    fn checked_cost(&self, distance: u64) -> Option<u64> {
+        Some(distance)
+    }
+
+    fn cost(&self, distance: u64) -> u64 {
+        distance
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    // The median is optimal for a linear cost
+    fn guess(&self, positions: &[u32]) -> u32 {
+        let mut positions = positions.to_vec();
+        let middle = positions.len() / 2;
+        *positions.select_nth_unstable(middle).1
+    }
+}
+
+pub(crate) struct Triangular;
+
+impl FuelCost for Triangular {
+    fn checked_cost(&self, distance: u64) -> Option<u64> {
+        distance
+            .checked_mul(distance + 1)
+            .map(|product| product / 2)
+    }
+
+    fn cost(&self, distance: u64) -> u64 {
+        gauss_sum(distance)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+pub(crate) struct Quadratic;
+
+impl FuelCost for Quadratic {
+    fn checked_cost(&self, distance: u64) -> Option<u64> {
+        distance.checked_mul(distance)
+    }
+
+    fn cost(&self, distance: u64) -> u64 {
+        distance * distance
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+// Any monotone cost, with the caller vouching for convexity
+pub(crate) struct Custom<F: Fn(u64) -> Option<u64>> {
+    pub(crate) cost: F,
+    pub(crate) convex: bool,
+}
+
+impl<F: Fn(u64) -> Option<u64>> FuelCost for Custom<F> {
+    fn checked_cost(&self, distance: u64) -> Option<u64> {
+        (self.cost)(distance)
+    }
+
+    fn is_convex(&self) -> bool {
+        self.convex
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Alignment data struct
 // -----------------------------------------------------------------------------
-fn part_1(positions: &Vec<u16>) -> crate::Result<u32> {
-    let median = positions[positions.len() / 2];
-    let fuel = positions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Alignment {
+    pub(crate) position: u32,
+    pub(crate) fuel: u64,
+}
+
+// Total fuel, summed in u64 unless the input range needs a wider accumulator
+fn total_fuel<C: FuelCost + ?Sized>(positions: &[u32], cost: &C, target: u32, wide: bool) -> u128 {
+    let distance = |position: &u32| position.abs_diff(target) as u64;
+    if wide {
+        positions
+            .iter()
+            .map(|position| cost.cost(distance(position)) as u128)
+            .sum()
+    } else {
+        positions
+            .iter()
+            .map(|position| cost.cost(distance(position)))
+            .sum::<u64>() as u128
+    }
+}
+
+// Whether the total fuel does not decrease from `target` to `target + 1`
+fn is_rising<C: FuelCost + ?Sized>(positions: &[u32], cost: &C, target: u32, wide: bool) -> bool {
+    if wide {
+        total_fuel(positions, cost, target, wide) <= total_fuel(positions, cost, target + 1, wide)
+    } else {
+        // Both totals in a single pass over the crabs
+        let (here, next) = positions.iter().fold((0, 0), |(here, next), position| {
+            let distance = position.abs_diff(target) as u64;
+            let distance_next = position.abs_diff(target + 1) as u64;
+            (here + cost.cost(distance), next + cost.cost(distance_next))
+        });
+        here <= next
+    }
+}
+
+// Cheapest position to align to, taking the lowest position on ties
+pub(crate) fn align<C: FuelCost + ?Sized>(positions: &[u32], cost: &C) -> crate::Result<Alignment> {
+    if positions.is_empty() {
+        return Err(crate::Error {
+            message: "no crab positions".to_string(),
+        });
+    }
+    let (min, max) = positions
         .iter()
-        .map(|position| (*position as i32 - median as i32).abs() as u32)
-        .sum();
-    Ok(fuel)
+        .fold((u32::MAX, 0), |(min, max), &position| {
+            (min.min(position), max.max(position))
+        });
+    // Costs are monotone, so the longest distance bounds every cost
+    let longest = (max - min) as u64;
+    let bound = cost.checked_cost(longest).ok_or(crate::Error {
+        message: format!("fuel to move a crab {} positions overflows", longest),
+    })?;
+    let wide = bound.checked_mul(positions.len() as u64).is_none();
+    let position = if cost.is_convex() {
+        // The total rises from the optimum on, so gallop out from the guess
+        // to bracket the first rising position, then bisect
+        let rising = |target: u32| target >= max || is_rising(positions, cost, target, wide);
+        let guess = cost.guess(positions);
+        let (mut low, mut high) = if rising(guess) {
+            let mut step = 1;
+            while guess - min > step && rising(guess - step) {
+                step = step.saturating_mul(2);
+            }
+            (guess.saturating_sub(step).max(min), guess)
+        } else {
+            let mut step = 1;
+            while max - guess > step && !rising(guess + step) {
+                step = step.saturating_mul(2);
+            }
+            (guess + 1, guess.saturating_add(step).min(max))
+        };
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if rising(middle) {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        low
+    } else {
+        (min..max + 1)
+            .min_by_key(|&target| total_fuel(positions, cost, target, wide))
+            .unwrap_or(min)
+    };
+    let fuel = total_fuel(positions, cost, position, wide);
+    Ok(Alignment {
+        position,
+        fuel: u64::try_from(fuel).map_err(|_| crate::Error {
+            message: format!("total fuel {} overflows", fuel),
+        })?,
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Part 1
+// -----------------------------------------------------------------------------
+fn part_1(positions: &[u32]) -> crate::Result<Alignment> {
+    align(positions, &Linear)
 }
 
 // -----------------------------------------------------------------------------
 // Part 2
 // -----------------------------------------------------------------------------
-fn part_2(positions: &Vec<u16>) -> crate::Result<u32> {
-    let average_float =
-        positions.iter().map(|n| *n as u32).sum::<u32>() as f32 / positions.len() as f32;
-    let average_floor = average_float.floor() as u32;
-    let average_ceil = average_float.ceil() as u32;
-    let (fuel_floor, fuel_ceil) = positions.iter().fold((0_u32, 0_u32), |acc, position| {
-        let distance_floor = (*position as i32 - average_floor as i32).abs() as u32;
-        let distance_ceil = (*position as i32 - average_ceil as i32).abs() as u32;
-        (
-            acc.0 + gauss_sum(distance_floor),
-            acc.1 + gauss_sum(distance_ceil),
-        )
-    });
-    Ok(std::cmp::min(fuel_floor, fuel_ceil))
+fn part_2(positions: &[u32]) -> crate::Result<Alignment> {
+    align(positions, &Triangular)
+}
+
+pub(crate) fn parse_positions(buffer: &str) -> crate::Result<Vec<u32>> {
+    Ok(buffer
+        .trim()
+        .split(',')
+        .map(|position| position.trim().parse())
+        .collect::<Result<_, _>>()?)
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    let power: Option<u32> = options.parse("power")?;
+    if !options.flag("align") && power.is_none() {
+        return Ok(());
+    }
+    let positions = parse_positions(buffer)?;
+    let mut models: Vec<(String, Box<dyn FuelCost>)> = vec![];
+    if options.flag("align") {
+        models.push(("Linear".to_string(), Box::new(Linear)));
+        models.push(("Triangular".to_string(), Box::new(Triangular)));
+        models.push(("Quadratic".to_string(), Box::new(Quadratic)));
+    }
+    if let Some(power) = power {
+        models.push((
+            format!("Power {}", power),
+            Box::new(Custom {
+                cost: move |distance: u64| distance.checked_pow(power),
+                convex: power >= 1,
+            }),
+        ));
+    }
+    for (name, cost) in models.iter() {
+        let alignment = align(&positions, cost.as_ref())?;
+        println!("    {}:", name.purple().bold());
+        println!("      Position: {}", alignment.position);
+        println!("      🦀 Fuel: {}", alignment.fuel);
+    }
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -53,43 +285,45 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Read to vector
     let start_setup = Instant::now();
-    let mut positions: Vec<u16> = buffer
-        .trim()
-        .split(',')
-        .map(|position| position.parse().expect("failed to parse position"))
-        .collect();
-    positions.sort_unstable();
+    let positions = parse_positions(&buffer)?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
     // Part 1
     // -------------------------------------------------------------------------
-    // Count fuel to median position
+    // Search for cheapest position with linear cost
     let start_part_1 = Instant::now();
-    let fuel_1 = part_1(&positions)?;
+    let alignment_1 = part_1(&positions)?;
     let time_part_1 = start_part_1.elapsed();
 
     // -------------------------------------------------------------------------
     // Part 2
     // -------------------------------------------------------------------------
-    // Count fuel to average position
+    // Search for cheapest position with triangular cost
     let start_part_2 = Instant::now();
-    let fuel_2 = part_2(&positions)?;
+    let alignment_2 = part_2(&positions)?;
     let time_part_2 = start_part_2.elapsed();
 
     // -------------------------------------------------------------------------
     // Return
     // -------------------------------------------------------------------------
+    let fuel = |alignment: Alignment| {
+        i64::try_from(alignment.fuel).map_err(|_| crate::Error {
+            message: format!("total fuel {} overflows", alignment.fuel),
+        })
+    };
+    let (position_1, position_2) = (alignment_1.position, alignment_2.position);
     Ok(RunData::new(
-        fuel_1 as i64,
-        fuel_2 as i64,
+        fuel(alignment_1)?,
+        fuel(alignment_2)?,
         Timing::new(
             time_setup,
             time_part_1,
             time_part_2,
             std::time::Duration::new(0, 0),
         ),
-    ))
+    )
+    .with_details(i64::from(position_1), i64::from(position_2)))
 }
 
 // -----------------------------------------------------------------------------
@@ -97,8 +331,11 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
 // -----------------------------------------------------------------------------
 pub(crate) fn report(run_data: &RunData) -> crate::Result<()> {
     output::print_day(7, "The Treachery of Whales")?;
+    let (position_1, position_2) = run_data.details.unwrap_or_default();
     output::print_part(1, "🦀 Fuel", &format!("{}", run_data.part_1))?;
+    output::print_detail("Position", &format!("{}", position_1))?;
     output::print_part(2, "🦀 Fuel", &format!("{}", run_data.part_2))?;
+    output::print_detail("Position", &format!("{}", position_2))?;
     output::print_timing(&run_data.times)?;
     Ok(())
 }
@@ -2,9 +2,24 @@
 //! For this puzzle I decided to parse all input into digits first. Pushing
 //! all of the work into the input parsing made the two questions trivial.
 //! To parse the final 4 digits, only the 1 and 4 of the initial digits are
-//! required. You don't need to identify all 10 digits.
+//! required, but recovering the whole wiring means bad entries get reported
+//! instead of quietly decoded. For the standard digits, each wire is matched
+//! to a segment by how many digits light it and which of the digits with a
+//! unique segment count light it.
+//! Other glyph tables, such as 14 or 16 segment alphanumeric displays, need a
+//! general solver. Each wire keeps a bitmask of the segments it could drive
+//! and each pattern a bitmask of the glyphs it could be, and pruning them
+//! against each other leaves only a small search.
+//! Checking every entry is not free. On my machine the setup for my input
+//! takes about 250µs with the signatures, against about 160µs for the old
+//! shortcut that only decoded the output with the 1 and 4, and about 1.3ms
+//! with the general solver, so I only use the general solver for other tables.
 
+use crate::cli::Options;
 use crate::prelude::*;
+use std::convert::TryFrom;
+
+pub(crate) const OPTIONS: &[&str] = &["wiring", "glyphs"];
 
 const NUMBER_DIGITS: usize = 4;
 
@@ -26,116 +41,595 @@ const NUMBER_DIGITS: usize = 4;
 // .    f  e    f  .    f  e    f  .    f
 // gggg    gggg    ....    gggg    gggg
 //
-// 0: has 6 segments, 3 overlapping with 4
-// 1: has 2 segments
-// 2: has 5 segments, last option
-// 3: has 5 segments, 2 overlapping with 1
-// 4: has 4 segments
-// 5: has 5 segments, 3 overlapping with 4
-// 6: has 6 segments, 1 overlapping with 1
-// 7: has 3 segments
-// 8: has 7 segments
-// 9: has 6 segments, last option
+// Segments lit for each standard digit, with bit 0 for segment a
+const SEGMENTS: usize = 7;
+const GLYPHS: [u64; 10] = [
+    0b1110111, 0b0100100, 0b1011101, 0b1101101, 0b0101110, 0b1101011, 0b1111011, 0b0100101,
+    0b1111111, 0b1101111,
+];
+const LABELS: [char; 10] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
 
-// -----------------------------------------------------------------------------
-// Display data struct
-// -----------------------------------------------------------------------------
-#[derive(Default, Debug)]
-struct Display {
-    digits: [u8; NUMBER_DIGITS],
+// The digits with a unique number of segments: 1, 4, 7 and 8
+const UNIQUE: [usize; 4] = [1, 4, 7, 8];
+const UNIQUE_GLYPHS: [u64; 4] = [GLYPHS[1], GLYPHS[4], GLYPHS[7], GLYPHS[8]];
+
+// Signature of each standard segment, and the digit lit by each set of
+// segments, with NOT_A_DIGIT for the rest
+const EXPECTED: [(u32, u32); SEGMENTS] = signatures(&GLYPHS, &UNIQUE_GLYPHS);
+const NOT_A_DIGIT: u8 = u8::MAX;
+const DIGITS: [u8; 1 << SEGMENTS] = digits();
+
+const fn digits() -> [u8; 1 << SEGMENTS] {
+    let mut digits = [NOT_A_DIGIT; 1 << SEGMENTS];
+    let mut digit = 0;
+    while digit < GLYPHS.len() {
+        digits[GLYPHS[digit] as usize] = digit as u8;
+        digit += 1;
+    }
+    digits
 }
 
-#[derive(Default, Debug)]
-struct KeyDigits {
-    one: u8,
-    four: u8,
+// Segments are lettered, and glyph sets fit in a u64
+const MAX_SEGMENTS: usize = 26;
+const MAX_GLYPHS: usize = 64;
+
+fn to_bits(s: &str, segments: usize) -> crate::Result<u64> {
+    let mut bits = 0_u64;
+    for b in s.bytes() {
+        let segment = b.wrapping_sub(b'a') as usize;
+        if segment >= segments {
+            return Err(crate::Error {
+                message: format!("invalid segment in pattern {}", s),
+            });
+        }
+        if bits & (1 << segment) != 0 {
+            return Err(crate::Error {
+                message: format!("repeated segment in pattern {}", s),
+            });
+        }
+        bits |= 1 << segment;
+    }
+    Ok(bits)
 }
 
-fn to_bits(s: &str) -> u8 {
-    s.as_bytes()
-        .iter()
-        .fold(0_u8, |acc, b| acc + (1 << (b - b'a') as usize))
+fn to_letters(bits: u64) -> String {
+    (0..MAX_SEGMENTS as u8)
+        .filter(|i| bits & (1 << i) != 0)
+        .map(|i| (b'a' + i) as char)
+        .collect()
 }
 
-impl std::str::FromStr for Display {
+// -----------------------------------------------------------------------------
+// Glyph table data struct
+// -----------------------------------------------------------------------------
+// Lines of a label and the segments it lights, such as `7 acf`
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct GlyphTable {
+    segments: usize,
+    labels: Vec<char>,
+    glyphs: Vec<u64>,
+    // Whether this is the table of standard digits, which has a fast solver
+    standard: bool,
+}
+
+impl std::str::FromStr for GlyphTable {
     type Err = crate::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut data = s.splitn(2, '|');
-        // Find identifier digits
-        let digit_key = data
-            .next()
-            .expect("failed to split line")
-            .splitn(11, ' ')
-            .take(10)
-            .fold(KeyDigits::default(), |mut key, digit| {
-                match digit.len() {
-                    2 => key.one = to_bits(digit),
-                    4 => key.four = to_bits(digit),
-                    _ => (),
-                };
-                key
+        let mut labels = vec![];
+        let mut glyphs = vec![];
+        for (i, line) in s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+        {
+            let error = |message: String| crate::Error {
+                message: format!("line {}: {}", i + 1, message),
+            };
+            let mut parts = line.split_whitespace();
+            let (label, pattern) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(label), Some(pattern), None) if label.chars().count() == 1 => {
+                    (label.chars().next().unwrap_or(' '), pattern)
+                }
+                _ => return Err(error("expected a label and a pattern".to_string())),
+            };
+            let glyph = to_bits(pattern, MAX_SEGMENTS).map_err(|e| error(e.message))?;
+            if labels.contains(&label) {
+                return Err(error(format!("repeated label {}", label)));
+            }
+            if glyphs.contains(&glyph) {
+                return Err(error(format!("repeated glyph {}", pattern)));
+            }
+            labels.push(label);
+            glyphs.push(glyph);
+        }
+        if glyphs.is_empty() || glyphs.len() > MAX_GLYPHS || glyphs.contains(&0) {
+            return Err(crate::Error {
+                message: format!("expected 1 to {} non-empty glyphs", MAX_GLYPHS),
             });
+        }
+        let segments = 64
+            - glyphs
+                .iter()
+                .fold(0, |all, glyph| all | glyph)
+                .leading_zeros();
+        Ok(Self {
+            segments: segments as usize,
+            standard: glyphs == GLYPHS && labels == LABELS,
+            labels,
+            glyphs,
+        })
+    }
+}
 
-        // Convert final 4 digits
-        let digits = data
-            .next()
-            .expect("failed to split line")
-            .splitn(5, ' ')
-            .skip(1)
-            .take(4)
-            .enumerate()
-            .fold([0; NUMBER_DIGITS], |mut digits, (i, digit)| {
-                let value = to_bits(digit);
-                digits[i] = match digit.len() {
-                    2 => 1,
-                    3 => 7,
-                    4 => 4,
-                    7 => 8,
-                    5 => {
-                        if (value & digit_key.one).count_ones() == 2 {
-                            3
-                        } else if (value & digit_key.four).count_ones() == 3 {
-                            5
-                        } else {
-                            2
-                        }
-                    }
-                    6 => {
-                        if (value & digit_key.one).count_ones() == 1 {
-                            6
-                        } else if (value & digit_key.four).count_ones() == 3 {
-                            0
-                        } else {
-                            9
-                        }
-                    }
-                    _ => unreachable!(),
+// What each wire or segment looks like from the patterns: how many it is lit
+// in, and a bit for each pattern with a unique number of segments that lights
+// it
+const fn signatures(patterns: &[u64], unique: &[u64]) -> [(u32, u32); SEGMENTS] {
+    let mut signatures = [(0, 0); SEGMENTS];
+    let mut segment = 0;
+    while segment < SEGMENTS {
+        let mut i = 0;
+        while i < patterns.len() {
+            signatures[segment].0 += (patterns[i] >> segment & 1) as u32;
+            i += 1;
+        }
+        i = 0;
+        while i < unique.len() {
+            signatures[segment].1 |= ((unique[i] >> segment & 1) as u32) << i;
+            i += 1;
+        }
+        segment += 1;
+    }
+    signatures
+}
+
+// Wire and glyph options for one entry
+#[derive(Clone)]
+struct Candidates {
+    // Segments each wire could drive
+    wires: Vec<u64>,
+    // Glyphs each pattern could be
+    patterns: Vec<u64>,
+}
+
+impl GlyphTable {
+    // The seven segment digits of the puzzle
+    pub(crate) fn standard() -> Self {
+        Self {
+            segments: SEGMENTS,
+            labels: LABELS.to_vec(),
+            glyphs: GLYPHS.to_vec(),
+            standard: true,
+        }
+    }
+
+    pub(crate) fn segments(&self) -> usize {
+        self.segments
+    }
+
+    pub(crate) fn label(&self, glyph: usize) -> char {
+        self.labels[glyph]
+    }
+
+    // Prune the options to a fixed point, false on a contradiction
+    fn propagate(&self, patterns: &[u64], candidates: &mut Candidates) -> bool {
+        let all_segments = u64::MAX >> (64 - self.segments);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (&pattern, options) in patterns.iter().zip(candidates.patterns.iter_mut()) {
+                // Glyphs that some choice of the wires could still light
+                let wires = &candidates.wires;
+                let reach = (0..self.segments)
+                    .filter(|wire| pattern & (1 << wire) != 0)
+                    .fold(0, |reach, wire| reach | wires[wire]);
+                let fits = |glyph: u64| {
+                    reach & glyph == glyph
+                        && (0..self.segments).all(|wire| {
+                            let allowed = if pattern & (1 << wire) != 0 {
+                                glyph
+                            } else {
+                                all_segments & !glyph
+                            };
+                            wires[wire] & allowed != 0
+                        })
                 };
-                digits
+                let pruned = self
+                    .glyphs
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, &glyph)| *options & (1 << i) != 0 && fits(glyph))
+                    .fold(0, |pruned, (i, _)| pruned | 1 << i);
+                if pruned == 0 {
+                    return false;
+                }
+                changed |= pruned != *options;
+                *options = pruned;
+
+                // Wires in the pattern drive a segment of one of its glyphs
+                let (inside, outside) = self
+                    .glyphs
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| pruned & (1 << i) != 0)
+                    .fold((0, 0), |(inside, outside), (_, &glyph)| {
+                        (inside | glyph, outside | (all_segments & !glyph))
+                    });
+                for (wire, segments) in candidates.wires.iter_mut().enumerate() {
+                    let allowed = if pattern & (1 << wire) != 0 {
+                        inside
+                    } else {
+                        outside
+                    };
+                    changed |= *segments & !allowed != 0;
+                    *segments &= allowed;
+                }
+            }
+            let wires = match distinct(&mut candidates.wires) {
+                Some(changed) => changed,
+                None => return false,
+            };
+            let glyphs = match distinct(&mut candidates.patterns) {
+                Some(changed) => changed,
+                None => return false,
+            };
+            changed |= wires || glyphs;
+        }
+        true
+    }
+
+    // Depth first search for up to two wirings
+    fn search(&self, patterns: &[u64], mut candidates: Candidates, found: &mut Vec<Wiring>) {
+        if found.len() > 1 || !self.propagate(patterns, &mut candidates) {
+            return;
+        }
+        let open = (0..self.segments)
+            .filter(|&wire| candidates.wires[wire].count_ones() > 1)
+            .min_by_key(|&wire| candidates.wires[wire].count_ones());
+        match open {
+            None => {
+                let mut wiring = Wiring {
+                    wires: self.segments,
+                    segments: [0; MAX_SEGMENTS],
+                };
+                for (segment, options) in wiring.segments.iter_mut().zip(candidates.wires.iter()) {
+                    *segment = options.trailing_zeros() as u8;
+                }
+                // Every pattern has to light a different glyph
+                let mut lit: Vec<u64> = patterns.iter().map(|&p| wiring.apply(p)).collect();
+                lit.sort_unstable();
+                lit.dedup();
+                if lit.len() == patterns.len() && lit.iter().all(|l| self.glyphs.contains(l)) {
+                    found.push(wiring);
+                }
+            }
+            Some(wire) => {
+                let options = candidates.wires[wire];
+                for segment in (0..self.segments).filter(|segment| options & (1 << segment) != 0) {
+                    let mut guess = candidates.clone();
+                    guess.wires[wire] = 1 << segment;
+                    self.search(patterns, guess, found);
+                }
+            }
+        }
+    }
+
+    // Match each wire of the standard digits to the segment with the same
+    // signature
+    fn solve_standard(&self, patterns: &[u64]) -> crate::Result<Wiring> {
+        // Glyphs with a unique number of segments pin down their patterns
+        let mut unique = [0; 4];
+        for (pattern, glyph) in unique.iter_mut().zip(UNIQUE_GLYPHS.iter()) {
+            let mut matches = patterns
+                .iter()
+                .filter(|pattern| pattern.count_ones() == glyph.count_ones());
+            *pattern = match (matches.next(), matches.next()) {
+                (Some(&pattern), None) => pattern,
+                _ => {
+                    return Err(crate::Error {
+                        message: format!(
+                            "expected exactly one pattern with {} segments",
+                            glyph.count_ones()
+                        ),
+                    })
+                }
+            };
+        }
+
+        let mut segments = [0; MAX_SEGMENTS];
+        let mut used = 0_u64;
+        let found = signatures(patterns, &unique);
+        for (wire, segment) in segments.iter_mut().take(SEGMENTS).enumerate() {
+            let mut candidates = EXPECTED
+                .iter()
+                .enumerate()
+                .filter(|(_, signature)| **signature == found[wire])
+                .map(|(segment, _)| segment);
+            *segment = match (candidates.next(), candidates.next()) {
+                (Some(candidate), None) => candidate as u8,
+                (None, _) => {
+                    return Err(crate::Error {
+                        message: format!("wire {} fits no segment", (b'a' + wire as u8) as char),
+                    })
+                }
+                (Some(_), Some(_)) => {
+                    return Err(crate::Error {
+                        message: format!(
+                            "wire {} fits more than one segment",
+                            (b'a' + wire as u8) as char
+                        ),
+                    })
+                }
+            };
+            if used & (1 << *segment) != 0 {
+                return Err(crate::Error {
+                    message: format!(
+                        "segment {} is driven by more than one wire",
+                        (b'a' + *segment) as char
+                    ),
+                });
+            }
+            used |= 1 << *segment;
+        }
+        Ok(Wiring {
+            wires: SEGMENTS,
+            segments,
+        })
+    }
+
+    // Recover the wiring from one scrambled pattern per glyph
+    pub(crate) fn solve(&self, patterns: &[u64]) -> crate::Result<Wiring> {
+        if patterns.len() != self.glyphs.len() {
+            return Err(crate::Error {
+                message: format!(
+                    "expected {} patterns, found {}",
+                    self.glyphs.len(),
+                    patterns.len()
+                ),
             });
-        Ok(Self { digits })
+        }
+        if self.standard {
+            return self.solve_standard(patterns);
+        }
+        let candidates = Candidates {
+            wires: vec![u64::MAX >> (64 - self.segments); self.segments],
+            patterns: patterns
+                .iter()
+                .map(|pattern| {
+                    self.glyphs
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, glyph)| glyph.count_ones() == pattern.count_ones())
+                        .fold(0, |options, (i, _)| options | 1 << i)
+                })
+                .collect(),
+        };
+        let mut found = vec![];
+        self.search(patterns, candidates, &mut found);
+        match found.len() {
+            0 => Err(crate::Error {
+                message: "patterns fit no wiring".to_string(),
+            }),
+            1 => Ok(found.remove(0)),
+            _ => Err(crate::Error {
+                message: "patterns fit more than one wiring".to_string(),
+            }),
+        }
+    }
+
+    // Glyph shown by a pattern of wires
+    pub(crate) fn glyph(&self, wiring: &Wiring, pattern: u64) -> crate::Result<usize> {
+        let lit = wiring.apply(pattern);
+        let glyph = if self.standard {
+            Some(DIGITS[lit as usize])
+                .filter(|&digit| digit != NOT_A_DIGIT)
+                .map(|digit| digit as usize)
+        } else {
+            self.glyphs.iter().position(|&glyph| glyph == lit)
+        };
+        glyph.ok_or_else(|| crate::Error {
+            message: format!("pattern {} is not a glyph", to_letters(pattern)),
+        })
+    }
+}
+
+// Wires take different segments and patterns take different glyphs, with as
+// many options as entries. Returns whether anything changed, or None on a
+// contradiction
+fn distinct(options: &mut [u64]) -> Option<bool> {
+    let mut changed = false;
+    // An option settled for one entry is gone for the rest
+    for i in 0..options.len() {
+        let settled = options[i];
+        if settled.count_ones() == 1 {
+            for (j, other) in options.iter_mut().enumerate() {
+                if j != i && *other & settled != 0 {
+                    *other &= !settled;
+                    changed = true;
+                }
+            }
+        }
+    }
+    // An option left to one entry belongs to it
+    let (once, twice) = options.iter().fold((0, 0), |(once, twice), &option| {
+        (once | option, twice | (once & option))
+    });
+    if once != u64::MAX >> (64 - options.len()) || options.contains(&0) {
+        return None;
+    }
+    for option in options.iter_mut() {
+        let only = *option & once & !twice;
+        if only != 0 && only != *option {
+            if only.count_ones() > 1 {
+                return None;
+            }
+            *option = only;
+            changed = true;
+        }
+    }
+    Some(changed)
+}
+
+// -----------------------------------------------------------------------------
+// Wiring data struct
+// -----------------------------------------------------------------------------
+// Segment driven by each wire, kept inline since every entry has a wiring
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Wiring {
+    wires: usize,
+    segments: [u8; MAX_SEGMENTS],
+}
+
+impl Wiring {
+    // Segments lit by a pattern of wires
+    pub(crate) fn apply(&self, pattern: u64) -> u64 {
+        let mut lit = 0;
+        let mut wires = pattern;
+        while wires != 0 {
+            lit |= 1 << self.segments[wires.trailing_zeros() as usize];
+            wires &= wires - 1;
+        }
+        lit
+    }
+}
+
+impl std::fmt::Display for Wiring {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let wires = to_letters(u64::MAX >> (64 - self.wires));
+        let segments: String = self.segments[..self.wires]
+            .iter()
+            .map(|&segment| (b'a' + segment) as char)
+            .collect();
+        write!(f, "{} -> {}", wires, segments)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Display data struct
+// -----------------------------------------------------------------------------
+#[derive(Debug)]
+pub(crate) struct Display {
+    pub(crate) wiring: Wiring,
+    // Scrambled pattern for each glyph
+    pub(crate) patterns: Vec<u64>,
+    // Glyphs of the output
+    pub(crate) digits: Vec<usize>,
+}
+
+fn parse_patterns(s: &str, table: &GlyphTable) -> crate::Result<Vec<u64>> {
+    let mut patterns = Vec::with_capacity(table.glyphs.len());
+    for pattern in s.split_ascii_whitespace() {
+        patterns.push(to_bits(pattern, table.segments())?);
+    }
+    if patterns.contains(&0) {
+        return Err(crate::Error {
+            message: "empty pattern".to_string(),
+        });
+    }
+    Ok(patterns)
+}
+
+impl std::str::FromStr for Display {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Display::decode(s, &GlyphTable::standard())
     }
 }
 
 impl Display {
+    pub(crate) fn decode(s: &str, table: &GlyphTable) -> crate::Result<Self> {
+        let (key, output) = s.split_once('|').ok_or_else(|| crate::Error {
+            message: "missing '|' between patterns and output".to_string(),
+        })?;
+        let mut patterns = parse_patterns(key, table)?;
+
+        // Solve the wiring, then check it turns every pattern into a
+        // different glyph, putting the patterns in glyph order
+        let wiring = table.solve(&patterns)?;
+        let mut ordered = [0; MAX_GLYPHS];
+        for &pattern in patterns.iter() {
+            let glyph = table.glyph(&wiring, pattern)?;
+            if ordered[glyph] != 0 {
+                return Err(crate::Error {
+                    message: format!("glyph {} appears more than once", table.label(glyph)),
+                });
+            }
+            ordered[glyph] = pattern;
+        }
+        let count = patterns.len();
+        patterns.copy_from_slice(&ordered[..count]);
+
+        // Convert the output, which is a four digit number on the standard
+        // displays
+        let mut digits = Vec::with_capacity(NUMBER_DIGITS);
+        for pattern in output.split_ascii_whitespace() {
+            digits.push(table.glyph(&wiring, to_bits(pattern, table.segments())?)?);
+        }
+        if digits.is_empty() {
+            return Err(crate::Error {
+                message: "missing output".to_string(),
+            });
+        }
+        if table.standard && digits.len() != NUMBER_DIGITS {
+            return Err(crate::Error {
+                message: format!(
+                    "expected {} output digits, found {}",
+                    NUMBER_DIGITS,
+                    digits.len()
+                ),
+            });
+        }
+        Ok(Self {
+            wiring,
+            patterns,
+            digits,
+        })
+    }
+
     fn count_simple_digits(&self) -> u8 {
         self.digits
             .iter()
-            .filter(|digit| [1, 4, 7, 8].contains(*digit))
+            .filter(|digit| UNIQUE.contains(*digit))
             .count() as u8
     }
 
-    fn digit_sum(&self) -> u32 {
-        self.digits
-            .iter()
-            .fold(0, |sum, curr| sum * 10 + *curr as u32)
+    fn digit_sum(&self) -> crate::Result<u64> {
+        self.digits.iter().try_fold(0_u64, |sum, &digit| {
+            sum.checked_mul(10)
+                .and_then(|sum| sum.checked_add(digit as u64))
+                .ok_or_else(|| crate::Error {
+                    message: "output value overflows".to_string(),
+                })
+        })
     }
 }
 
+fn add_output(total: u64, display: &Display) -> crate::Result<u64> {
+    total
+        .checked_add(display.digit_sum()?)
+        .ok_or_else(|| crate::Error {
+            message: "sum of the output values overflows".to_string(),
+        })
+}
+
+fn parse_display(index: usize, line: &str, table: &GlyphTable) -> crate::Result<Display> {
+    Display::decode(line, table).map_err(|error| crate::Error {
+        message: format!("line {}: {}", index + 1, error.message),
+    })
+}
+
+pub(crate) fn parse_displays(buffer: &str, table: &GlyphTable) -> crate::Result<Vec<Display>> {
+    buffer
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse_display(i, line, table))
+        .collect()
+}
+
 // -----------------------------------------------------------------------------
 // Part 1
 // -----------------------------------------------------------------------------
-fn part_1(positions: &Vec<Display>) -> crate::Result<u32> {
+fn part_1(positions: &[Display]) -> crate::Result<u32> {
     Ok(positions
         .iter()
         .map(|display| display.count_simple_digits() as u32)
@@ -145,20 +639,58 @@ fn part_1(positions: &Vec<Display>) -> crate::Result<u32> {
 // -----------------------------------------------------------------------------
 // Part 2
 // -----------------------------------------------------------------------------
-fn part_2(positions: &Vec<Display>) -> crate::Result<u32> {
-    Ok(positions.iter().map(|display| display.digit_sum()).sum())
+fn part_2(positions: &[Display]) -> crate::Result<u64> {
+    positions.iter().try_fold(0, add_output)
 }
 
 // -----------------------------------------------------------------------------
 // Combined
 // -----------------------------------------------------------------------------
-fn combined(positions: &mut dyn Iterator<Item = Display>) -> crate::Result<(u32, u32)> {
-    Ok(positions.fold((0, 0), |acc, display| {
-        (
-            acc.0 + display.count_simple_digits() as u32,
-            acc.1 + display.digit_sum(),
-        )
-    }))
+fn combined(
+    positions: &mut dyn Iterator<Item = crate::Result<Display>>,
+) -> crate::Result<(u32, u64)> {
+    let mut acc = (0, 0);
+    for display in positions {
+        let display = display?;
+        acc.0 += display.count_simple_digits() as u32;
+        acc.1 = add_output(acc.1, &display)?;
+    }
+    Ok(acc)
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    if !options.flag("wiring") && options.value("glyphs").is_none() {
+        return Ok(());
+    }
+    let table = match options.value("glyphs") {
+        Some(path) => crate::load::data_to_buffer(path.to_string())?.parse()?,
+        None => GlyphTable::standard(),
+    };
+    println!("    {}:", "Wiring".purple().bold());
+    for (i, display) in parse_displays(buffer, &table)?.iter().enumerate() {
+        let output: String = display
+            .digits
+            .iter()
+            .map(|&glyph| table.label(glyph))
+            .collect();
+        println!(
+            "      Entry {}: {}, output {}",
+            i + 1,
+            display.wiring,
+            output
+        );
+        let patterns: Vec<String> = display
+            .patterns
+            .iter()
+            .enumerate()
+            .map(|(glyph, &pattern)| format!("{}={}", table.label(glyph), to_letters(pattern)))
+            .collect();
+        println!("        {}", patterns.join(" "));
+    }
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -170,10 +702,8 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Read to vector
     let start_setup = Instant::now();
-    let displays: Vec<Display> = buffer
-        .lines()
-        .map(|line| line.parse().expect("failed to decode line"))
-        .collect();
+    let table = GlyphTable::standard();
+    let displays = parse_displays(&buffer, &table)?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -198,7 +728,8 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     let start_combined = Instant::now();
     let mut displays = buffer
         .lines()
-        .map(|line| line.parse().expect("failed to decode line"));
+        .enumerate()
+        .map(|(i, line)| parse_display(i, line, &table));
     let (count_combined, sum_combined) = combined(&mut displays)?;
     let time_combined = start_combined.elapsed();
     assert_eq!(count_1, count_combined);
@@ -209,7 +740,9 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     Ok(RunData::new(
         count_1 as i64,
-        sum_2 as i64,
+        i64::try_from(sum_2).map_err(|_| crate::Error {
+            message: format!("sum of the output values {} overflows", sum_2),
+        })?,
         Timing::new(time_setup, time_part_1, time_part_2, time_combined),
     ))
 }
@@ -1,39 +1,64 @@
 //! Day 9:
 //! Using the overset grid to homogonize operations helped efficiency today.
 //! Without this overset, special logic would be needed to handle the edges and
-//! corners of the smoke map. For the second part, I used a flow fill algorithm
-//! changing the values so the recursion worked correctly.
+//! corners of the smoke map. For the second part, I used a flood fill algorithm
+//! changing the values so each point is only counted once. The fill uses an
+//! explicit stack rather than recursion so large maps can't overflow the call
+//! stack. Labeling the basins into a separate grid leaves the smoke map intact,
+//! which lets the basins be drawn to the terminal or to an image.
 
+use crate::cli::Options;
+use crate::grid::Grid;
 use crate::prelude::*;
+use std::convert::TryFrom;
+
+pub(crate) const OPTIONS: &[&str] = &["render", "ppm", "scale"];
+const DEFAULT_SCALE: usize = 4;
 
 // -----------------------------------------------------------------------------
 // Coordinate struct
 // -----------------------------------------------------------------------------
-#[derive(Default, Debug)]
-struct Point {
-    x: u8,
-    y: u8,
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Point {
+    pub(crate) x: usize,
+    pub(crate) y: usize,
+}
+
+// -----------------------------------------------------------------------------
+// Basin data struct
+// -----------------------------------------------------------------------------
+#[derive(Debug)]
+pub(crate) struct Basin {
+    pub(crate) low_point: Point,
+    pub(crate) cells: Vec<Point>,
+}
+
+impl Basin {
+    pub(crate) fn size(&self) -> usize {
+        self.cells.len()
+    }
 }
 
 // -----------------------------------------------------------------------------
 // Part 1
 // -----------------------------------------------------------------------------
-fn part_1(smoke: &Vec<u8>, width: usize) -> crate::Result<(u32, Vec<Point>)> {
+fn part_1(smoke: &Grid<u8>) -> crate::Result<(u32, Vec<Point>)> {
     let mut risk_sum = 0;
     let mut low_points = Vec::with_capacity(200);
-    (1..width - 1).for_each(|j| {
-        (1..width - 1).for_each(|i| {
-            let current = smoke[i + j * width];
-            if current < smoke[(i + 1) + j * width]
-                && current < smoke[(i - 1) + j * width]
-                && current < smoke[i + (j + 1) * width]
-                && current < smoke[i + (j - 1) * width]
+    let stride = smoke.stride();
+    let (width, height) = (smoke.width(), smoke.height());
+    let smoke = smoke.cells();
+    (1..height + 1).for_each(|j| {
+        (1..width + 1).for_each(|i| {
+            let index = i + j * stride;
+            let current = smoke[index];
+            if current < smoke[index + 1]
+                && current < smoke[index - 1]
+                && current < smoke[index + stride]
+                && current < smoke[index - stride]
             {
                 risk_sum += current as u32 + 1;
-                low_points.push(Point {
-                    x: i as u8,
-                    y: j as u8,
-                });
+                low_points.push(Point { x: i - 1, y: j - 1 });
             }
         })
     });
@@ -43,25 +68,149 @@ fn part_1(smoke: &Vec<u8>, width: usize) -> crate::Result<(u32, Vec<Point>)> {
 // -----------------------------------------------------------------------------
 // Part 2
 // -----------------------------------------------------------------------------
-fn find_basin_size(i: usize, j: usize, width: usize, smoke: &mut Vec<u8>) -> u32 {
-    if smoke[i + j * width] == 9 {
-        0
-    } else {
-        smoke[i + j * width] = 9;
-        1 + find_basin_size(i + 1, j, width, smoke)
-            + find_basin_size(i - 1, j, width, smoke)
-            + find_basin_size(i, j + 1, width, smoke)
-            + find_basin_size(i, j - 1, width, smoke)
+fn find_basin_size(index: usize, smoke: &mut Grid<u8>, stack: &mut Vec<usize>) -> u64 {
+    let offsets = smoke.offsets_4();
+    let mut size = 0;
+    smoke[index] = 9;
+    stack.push(index);
+    while let Some(current) = stack.pop() {
+        size += 1;
+        offsets.iter().for_each(|offset| {
+            let neighbour = current.wrapping_add(*offset);
+            if smoke[neighbour] != 9 {
+                smoke[neighbour] = 9;
+                stack.push(neighbour);
+            }
+        });
     }
+    size
 }
 
-fn part_2(smoke: &mut Vec<u8>, low_points: Vec<Point>, width: usize) -> crate::Result<u32> {
-    let mut basin_sizes: Vec<u32> = low_points
+fn part_2(smoke: &mut Grid<u8>, low_points: Vec<Point>) -> crate::Result<u64> {
+    let mut stack = Vec::with_capacity(128);
+    let mut basin_sizes: Vec<u64> = low_points
         .iter()
-        .map(|point| find_basin_size(point.x as usize, point.y as usize, width, smoke))
+        .map(|point| {
+            let index = smoke.index(point.x, point.y);
+            find_basin_size(index, smoke, &mut stack)
+        })
         .collect();
     basin_sizes.sort_unstable_by(|a, b| b.cmp(a));
-    Ok(basin_sizes.iter().take(3).product())
+    basin_sizes
+        .iter()
+        .take(3)
+        .try_fold(1_u64, |product, &size| product.checked_mul(size))
+        .ok_or_else(|| crate::Error {
+            message: "product of the basin sizes overflows".to_string(),
+        })
+}
+
+// -----------------------------------------------------------------------------
+// Basin labeling
+// -----------------------------------------------------------------------------
+pub(crate) fn label_basins(smoke: &Grid<u8>) -> crate::Result<(Grid<u32>, Vec<Basin>)> {
+    let (_, low_points) = part_1(smoke)?;
+    let mut labels = Grid::new(smoke.width(), smoke.height(), 0_u32, 0_u32);
+    let mut stack = Vec::with_capacity(128);
+    let basins = low_points
+        .iter()
+        .enumerate()
+        .map(|(i, low_point)| {
+            let label = i as u32 + 1;
+            let mut cells = vec![];
+            let start = smoke.index(low_point.x, low_point.y);
+            labels[start] = label;
+            stack.push(start);
+            while let Some(current) = stack.pop() {
+                let (x, y) = smoke.coordinates(current);
+                cells.push(Point { x, y });
+                smoke.neighbours_4(current).for_each(|neighbour| {
+                    if smoke[neighbour] != 9 && labels[neighbour] == 0 {
+                        labels[neighbour] = label;
+                        stack.push(neighbour);
+                    }
+                });
+            }
+            Basin {
+                low_point: *low_point,
+                cells,
+            }
+        })
+        .collect();
+    Ok((labels, basins))
+}
+
+// -----------------------------------------------------------------------------
+// Basin rendering
+// -----------------------------------------------------------------------------
+fn render_basins(smoke: &Grid<u8>, labels: &Grid<u32>) {
+    (0..smoke.height()).for_each(|y| {
+        let row: String = (0..smoke.width())
+            .map(|x| {
+                let height = (b'0' + smoke.get(x, y)) as char;
+                match labels.get(x, y) {
+                    0 => height.to_string().dimmed().to_string(),
+                    label => {
+                        let (r, g, b) = output::palette(label as usize - 1);
+                        height.to_string().truecolor(r, g, b).bold().to_string()
+                    }
+                }
+            })
+            .collect();
+        println!("    {}", row);
+    });
+}
+
+fn basin_pixels(smoke: &Grid<u8>, labels: &Grid<u32>, basins: &[Basin]) -> Vec<(u8, u8, u8)> {
+    let mut pixels: Vec<(u8, u8, u8)> = smoke
+        .interior_indices()
+        .map(|index| match (smoke[index], labels[index]) {
+            (9, _) => (32, 32, 32),
+            (_, 0) => (255, 255, 255),
+            (height, label) => {
+                // Shade each basin from light at the bottom to dark at the rim
+                let (r, g, b) = output::palette(label as usize - 1);
+                let shade = |channel: u8| (channel as u32 * (16 - height as u32) / 16) as u8;
+                (shade(r), shade(g), shade(b))
+            }
+        })
+        .collect();
+    basins.iter().for_each(|basin| {
+        let point = basin.low_point;
+        pixels[point.x + point.y * smoke.width()] = (255, 255, 255)
+    });
+    pixels
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    if !options.flag("render") && options.value("ppm").is_none() {
+        return Ok(());
+    }
+    let smoke = Grid::from_digits(buffer, 9_u8)?;
+    let (labels, mut basins) = label_basins(&smoke)?;
+    println!("    {}:", "Basins".purple().bold());
+    println!("      Count: {}", basins.len());
+    basins.sort_by_key(|basin| std::cmp::Reverse(basin.size()));
+    basins.iter().take(3).for_each(|basin| {
+        println!(
+            "      Low point ({}, {}): {} cells",
+            basin.low_point.x,
+            basin.low_point.y,
+            basin.size()
+        )
+    });
+    if options.flag("render") {
+        render_basins(&smoke, &labels);
+    }
+    if let Some(path) = options.value("ppm") {
+        let scale = options.parse("scale")?.unwrap_or(DEFAULT_SCALE);
+        let pixels = basin_pixels(&smoke, &labels, &basins);
+        output::write_ppm(path, smoke.width(), smoke.height(), scale, &pixels)?;
+    }
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -71,16 +220,9 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Setup
     // -------------------------------------------------------------------------
-    // Read to vector
+    // Read to grid
     let start_setup = Instant::now();
-    let width = buffer.lines().nth(0).expect("failed to load input").len() + 2;
-    let mut smoke = vec![9; width * width];
-    buffer.lines().enumerate().for_each(|(j, line)| {
-        line.as_bytes()
-            .iter()
-            .enumerate()
-            .for_each(|(i, height)| smoke[(i + 1) + (j + 1) * width] = *height - b'0')
-    });
+    let mut smoke = Grid::from_digits(&buffer, 9_u8)?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -88,7 +230,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Look for low points
     let start_part_1 = Instant::now();
-    let (sum_1, low_points) = part_1(&smoke, width)?;
+    let (sum_1, low_points) = part_1(&smoke)?;
     let time_part_1 = start_part_1.elapsed();
 
     // -------------------------------------------------------------------------
@@ -96,7 +238,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Count the size of each basin around a low point
     let start_part_2 = Instant::now();
-    let product_2 = part_2(&mut smoke, low_points, width)?;
+    let product_2 = part_2(&mut smoke, low_points)?;
     let time_part_2 = start_part_2.elapsed();
 
     // -------------------------------------------------------------------------
@@ -104,7 +246,9 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     Ok(RunData::new(
         sum_1 as i64,
-        product_2 as i64,
+        i64::try_from(product_2).map_err(|_| crate::Error {
+            message: format!("product of the basin sizes {} overflows", product_2),
+        })?,
         Timing::new(
             time_setup,
             time_part_1,
@@ -1,103 +1,437 @@
 //! Day 10:
 //! I used the typical algorith for brace matches. The slowest part of this
 //! approch is the parsing of each line, with the dynamic creation of the vector
-//! of opening braces.
+//! of opening braces. Each line is now classified as valid, corrupted or
+//! incomplete, keeping the column of the bad closer and the string that
+//! completes the line, which is enough for compiler style diagnostics. The
+//! brackets and their scores come from a grammar, one pair per line, so the
+//! matcher no longer leans on the ASCII codes of `()[]{}<>` being close
+//! together, and any characters work as brackets, even `«»`. Scanning the
+//! pairs for every character more than doubled the setup time, so the grammar
+//! keeps a table from ASCII bytes to brackets and a map for anything else.
 
+use crate::cli::Options;
 use crate::prelude::*;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+pub(crate) const OPTIONS: &[&str] = &["diagnostics", "grammar"];
+
+// The puzzle brackets, as an opener, a closer, the score of a corrupted line
+// and the completion score
+const DEFAULT_GRAMMAR: &str = "( ) 3 1
+[ ] 57 2
+{ } 1197 3
+< > 25137 4";
+
+// Each closer in a completion multiplies the score so far
+const COMPLETION_BASE: u64 = 5;
 
 // -----------------------------------------------------------------------------
-// Direction data struct
+// Grammar data struct
 // -----------------------------------------------------------------------------
-#[derive(Default, Debug)]
-struct NavigationLine {
-    characters: Vec<u8>,
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Pair {
+    pub(crate) open: char,
+    pub(crate) close: char,
+    pub(crate) mismatch: u64,
+    pub(crate) missing: u64,
 }
 
-impl std::str::FromStr for NavigationLine {
+// Role of a character, as the index of its pair
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Bracket {
+    Open(usize),
+    Close(usize),
+}
+
+// ASCII brackets are looked up by byte, anything else by character
+const ASCII: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Grammar {
+    pairs: Vec<Pair>,
+    ascii: [Option<Bracket>; ASCII],
+    others: HashMap<char, Bracket>,
+}
+
+impl Default for Grammar {
+    fn default() -> Self {
+        DEFAULT_GRAMMAR.parse().expect("default grammar is valid")
+    }
+}
+
+impl std::str::FromStr for Grammar {
     type Err = crate::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut characters = Vec::with_capacity(20);
-        s.as_bytes().iter().find(|character| match **character {
-            b'(' | b'[' | b'{' | b'<' => {
-                characters.push(**character);
-                false
+        let mut pairs: Vec<Pair> = vec![];
+        for (i, line) in s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+        {
+            let error = |message: &str| crate::Error {
+                message: format!("line {}: {}", i + 1, message),
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 4 {
+                return Err(error("expected an opener, a closer and two scores"));
             }
-            b')' | b']' | b'}' | b'>' => {
-                let last_opening = characters.pop().unwrap_or(u8::MAX);
-                if last_opening > **character || **character - last_opening > 2 {
-                    characters = vec![**character];
-                    true
-                } else {
-                    false
+            let bracket = |field: &str| {
+                let mut characters = field.chars();
+                match (characters.next(), characters.next()) {
+                    (Some(character), None) => Ok(character),
+                    _ => Err(error("brackets are single characters")),
                 }
+            };
+            let pair = Pair {
+                open: bracket(fields[0])?,
+                close: bracket(fields[1])?,
+                mismatch: fields[2]
+                    .parse()
+                    .map_err(|_| error("scores are non-negative integers"))?,
+                missing: fields[3]
+                    .parse()
+                    .map_err(|_| error("scores are non-negative integers"))?,
+            };
+            let used = |character: char| {
+                pair.open == pair.close
+                    || pairs
+                        .iter()
+                        .any(|other| other.open == character || other.close == character)
+            };
+            if used(pair.open) || used(pair.close) {
+                return Err(error("brackets are used more than once"));
             }
-            _ => unreachable!(),
-        });
-        Ok(Self { characters })
+            pairs.push(pair);
+        }
+        if pairs.is_empty() {
+            return Err(crate::Error {
+                message: "grammar has no bracket pairs".to_string(),
+            });
+        }
+        let mut ascii = [None; ASCII];
+        let mut others = HashMap::new();
+        for (i, pair) in pairs.iter().enumerate() {
+            for (character, bracket) in [
+                (pair.open, Bracket::Open(i)),
+                (pair.close, Bracket::Close(i)),
+            ] {
+                match ascii.get_mut(character as usize) {
+                    Some(entry) => *entry = Some(bracket),
+                    None => {
+                        others.insert(character, bracket);
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            pairs,
+            ascii,
+            others,
+        })
+    }
+}
+
+impl Grammar {
+    fn bracket(&self, character: char) -> Option<Bracket> {
+        match self.ascii.get(character as usize) {
+            Some(&bracket) => bracket,
+            None => self.others.get(&character).copied(),
+        }
+    }
+
+    fn closing(&self, character: char) -> Option<&Pair> {
+        match self.bracket(character) {
+            Some(Bracket::Close(pair)) => Some(&self.pairs[pair]),
+            _ => None,
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Navigation line data struct
+// -----------------------------------------------------------------------------
+#[derive(Debug, PartialEq)]
+pub(crate) enum Status {
+    Valid,
+    // Columns count characters from 1, and nothing is expected when no chunk
+    // is open
+    Corrupted {
+        column: usize,
+        expected: Option<char>,
+        found: char,
+    },
+    Incomplete {
+        completion: String,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct NavigationLine {
+    pub(crate) status: Status,
+}
+
+impl std::str::FromStr for NavigationLine {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NavigationLine::check(s, &Grammar::default())
     }
 }
 
 impl NavigationLine {
-    fn score_mismatch(&self) -> u32 {
-        match self.characters[0] {
-            b')' => 3,
-            b']' => 57,
-            b'}' => 1197,
-            b'>' => 25137,
+    pub(crate) fn check(s: &str, grammar: &Grammar) -> crate::Result<Self> {
+        // Pairs of the open chunks
+        let mut open = Vec::with_capacity(20);
+        for (i, character) in s.chars().enumerate() {
+            match grammar.bracket(character) {
+                Some(Bracket::Open(pair)) => open.push(pair),
+                Some(Bracket::Close(pair)) => {
+                    let expected = open.pop();
+                    if expected != Some(pair) {
+                        return Ok(Self {
+                            status: Status::Corrupted {
+                                column: i + 1,
+                                expected: expected.map(|pair| grammar.pairs[pair].close),
+                                found: character,
+                            },
+                        });
+                    }
+                }
+                None => {
+                    return Err(crate::Error {
+                        message: format!("column {}: unexpected character {:?}", i + 1, character),
+                    })
+                }
+            }
+        }
+        let status = if open.is_empty() {
+            Status::Valid
+        } else {
+            Status::Incomplete {
+                completion: open
+                    .iter()
+                    .rev()
+                    .map(|&pair| grammar.pairs[pair].close)
+                    .collect(),
+            }
+        };
+        Ok(Self { status })
+    }
+
+    fn score_mismatch(&self, grammar: &Grammar) -> u64 {
+        match self.status {
+            Status::Corrupted { found, .. } => grammar
+                .closing(found)
+                .map(|pair| pair.mismatch)
+                .unwrap_or(0),
             _ => 0,
         }
     }
 
-    fn score_missing(&self) -> u64 {
-        self.characters.iter().rev().fold(0, |score, current| {
-            score * 5
-                + match current {
-                    b'(' => 1,
-                    b'[' => 2,
-                    b'{' => 3,
-                    b'<' => 4,
-                    _ => 0,
-                }
-        })
+    fn score_missing(&self, grammar: &Grammar) -> crate::Result<u64> {
+        match &self.status {
+            Status::Incomplete { completion } => {
+                completion.chars().try_fold(0_u64, |score, current| {
+                    let value = grammar
+                        .closing(current)
+                        .map(|pair| pair.missing)
+                        .unwrap_or(0);
+                    score
+                        .checked_mul(COMPLETION_BASE)
+                        .and_then(|score| score.checked_add(value))
+                        .ok_or_else(|| crate::Error {
+                            message: format!("completion score of {} overflows", completion),
+                        })
+                })
+            }
+            _ => Ok(0),
+        }
     }
 }
 
+fn parse_line(index: usize, line: &str, grammar: &Grammar) -> crate::Result<NavigationLine> {
+    NavigationLine::check(line, grammar).map_err(|error| crate::Error {
+        message: format!("line {}: {}", index + 1, error.message),
+    })
+}
+
+pub(crate) fn parse_lines(buffer: &str, grammar: &Grammar) -> crate::Result<Vec<NavigationLine>> {
+    buffer
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse_line(i, line, grammar))
+        .collect()
+}
+
+// Running total of the corrupted line scores
+fn add_mismatch(total: u64, score: u64) -> crate::Result<u64> {
+    total.checked_add(score).ok_or_else(|| crate::Error {
+        message: "total score of the corrupted lines overflows".to_string(),
+    })
+}
+
+// Middle of the completion scores
+fn middle_score(mut scores: Vec<u64>) -> crate::Result<u64> {
+    if scores.is_empty() {
+        return Err(crate::Error {
+            message: "no incomplete lines".to_string(),
+        });
+    }
+    scores.sort_unstable();
+    Ok(scores[scores.len() / 2])
+}
+
 // -----------------------------------------------------------------------------
 // Part 1
 // -----------------------------------------------------------------------------
-fn part_1(chunks: &Vec<NavigationLine>) -> crate::Result<u32> {
-    Ok(chunks.iter().map(|chunk| chunk.score_mismatch()).sum())
+fn part_1(chunks: &[NavigationLine], grammar: &Grammar) -> crate::Result<u64> {
+    chunks.iter().try_fold(0_u64, |total, chunk| {
+        add_mismatch(total, chunk.score_mismatch(grammar))
+    })
 }
 
 // -----------------------------------------------------------------------------
 // Part 2
 // -----------------------------------------------------------------------------
-fn part_2(chunks: &Vec<NavigationLine>) -> crate::Result<u64> {
-    let mut scores: Vec<u64> = chunks
-        .iter()
-        .map(|chunk| chunk.score_missing())
-        .filter(|score| *score != 0)
-        .collect();
-    scores.sort_unstable();
-    Ok(scores[scores.len() / 2])
+fn part_2(chunks: &[NavigationLine], grammar: &Grammar) -> crate::Result<u64> {
+    middle_score(
+        chunks
+            .iter()
+            .filter(|chunk| matches!(chunk.status, Status::Incomplete { .. }))
+            .map(|chunk| chunk.score_missing(grammar))
+            .collect::<crate::Result<_>>()?,
+    )
 }
 
 // -----------------------------------------------------------------------------
 // Combined
 // -----------------------------------------------------------------------------
-fn combined(chunks: &mut dyn Iterator<Item = NavigationLine>) -> crate::Result<(u32, u64)> {
+fn combined(
+    chunks: &mut dyn Iterator<Item = crate::Result<NavigationLine>>,
+    grammar: &Grammar,
+) -> crate::Result<(u64, u64)> {
     let mut score_mismatch = 0;
     let mut scores_missing = Vec::with_capacity(20);
-    chunks.for_each(|chunk| {
-        if chunk.characters.len() == 1 {
-            score_mismatch += chunk.score_mismatch();
-        } else {
-            scores_missing.push(chunk.score_missing());
+    for chunk in chunks {
+        let chunk = chunk?;
+        match chunk.status {
+            Status::Corrupted { .. } => {
+                score_mismatch = add_mismatch(score_mismatch, chunk.score_mismatch(grammar))?
+            }
+            Status::Incomplete { .. } => scores_missing.push(chunk.score_missing(grammar)?),
+            Status::Valid => (),
         }
-    });
-    scores_missing.sort_unstable();
-    let score_missing = scores_missing[scores_missing.len() / 2];
-    Ok((score_mismatch, score_missing))
+    }
+    Ok((score_mismatch, middle_score(scores_missing)?))
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+// Both scores under any grammar
+pub(crate) fn scores(chunks: &[NavigationLine], grammar: &Grammar) -> crate::Result<(u64, u64)> {
+    Ok((part_1(chunks, grammar)?, part_2(chunks, grammar)?))
+}
+
+// Compiler style message with a caret under the column
+fn diagnostic(number: usize, line: &str, column: usize, headline: String, label: String) {
+    let gutter = format!("      {}", " ".repeat(number.to_string().len()));
+    println!("      {}", headline);
+    println!(
+        "{}{} line {}:{}",
+        gutter,
+        "-->".blue().bold(),
+        number,
+        column
+    );
+    println!("{} {}", gutter, "|".blue().bold());
+    println!(
+        "      {} {} {}",
+        number.to_string().blue().bold(),
+        "|".blue().bold(),
+        line
+    );
+    println!(
+        "{} {} {}{}",
+        gutter,
+        "|".blue().bold(),
+        " ".repeat(column - 1),
+        label
+    );
+}
+
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    let grammar = match options.value("grammar") {
+        Some(path) => crate::load::data_to_buffer(path.to_string())?.parse()?,
+        None => Grammar::default(),
+    };
+    let chunks = parse_lines(buffer, &grammar)?;
+    if options.value("grammar").is_some() {
+        let (corrupted, completion) = scores(&chunks, &grammar)?;
+        println!("    {}:", "Grammar".purple().bold());
+        println!("      Corrupted score: {}", corrupted);
+        println!("      Completion score: {}", completion);
+    }
+    if !options.flag("diagnostics") {
+        return Ok(());
+    }
+    println!("    {}:", "Diagnostics".purple().bold());
+    for (i, (line, chunk)) in buffer.lines().zip(chunks.iter()).enumerate() {
+        let number = i + 1;
+        match &chunk.status {
+            Status::Valid => (),
+            Status::Corrupted {
+                column,
+                expected,
+                found,
+            } => {
+                let (headline, label) = match expected {
+                    Some(expected) => (
+                        format!("expected `{}`, found `{}`", expected, found),
+                        format!("^ expected `{}`", expected),
+                    ),
+                    None => (
+                        format!("unexpected `{}`, no chunk is open", found),
+                        "^ unexpected closer".to_string(),
+                    ),
+                };
+                let headline = format!("{}: {}", "error".red().bold(), headline.bold());
+                diagnostic(
+                    number,
+                    line,
+                    *column,
+                    headline,
+                    label.red().bold().to_string(),
+                );
+            }
+            Status::Incomplete { completion } => {
+                let headline = format!(
+                    "{}: {}",
+                    "warning".yellow().bold(),
+                    "incomplete line".bold()
+                );
+                let label = format!("^ complete with `{}`", completion);
+                let column = line.chars().count() + 1;
+                diagnostic(
+                    number,
+                    line,
+                    column,
+                    headline,
+                    label.yellow().bold().to_string(),
+                );
+            }
+        }
+    }
+    let count =
+        |valid: fn(&Status) -> bool| chunks.iter().filter(|chunk| valid(&chunk.status)).count();
+    println!(
+        "      {} valid, {} corrupted, {} incomplete",
+        count(|status| *status == Status::Valid),
+        count(|status| matches!(status, Status::Corrupted { .. })),
+        count(|status| matches!(status, Status::Incomplete { .. })),
+    );
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -109,10 +443,8 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Read to vector
     let start_setup = Instant::now();
-    let chunks: Vec<NavigationLine> = buffer
-        .lines()
-        .map(|line| line.parse().expect("failed to parse line"))
-        .collect();
+    let grammar = Grammar::default();
+    let chunks = parse_lines(&buffer, &grammar)?;
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -120,7 +452,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Score brace mismatches
     let start_part_1 = Instant::now();
-    let score_1 = part_1(&chunks)?;
+    let score_1 = part_1(&chunks, &grammar)?;
     let time_part_1 = start_part_1.elapsed();
 
     // -------------------------------------------------------------------------
@@ -128,7 +460,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Score incomplete lines
     let start_part_2 = Instant::now();
-    let score_2 = part_2(&chunks)?;
+    let score_2 = part_2(&chunks, &grammar)?;
     let time_part_2 = start_part_2.elapsed();
 
     // -------------------------------------------------------------------------
@@ -137,8 +469,9 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     let start_combined = Instant::now();
     let mut chunks = buffer
         .lines()
-        .map(|line| line.parse().expect("failed to parse line"));
-    let (score_1_combined, score_2_combined) = combined(&mut chunks)?;
+        .enumerate()
+        .map(|(i, line)| parse_line(i, line, &grammar));
+    let (score_1_combined, score_2_combined) = combined(&mut chunks, &grammar)?;
     let time_combined = start_combined.elapsed();
     assert_eq!(score_1, score_1_combined);
     assert_eq!(score_2, score_2_combined);
@@ -147,8 +480,12 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // Return
     // -------------------------------------------------------------------------
     Ok(RunData::new(
-        score_1 as i64,
-        score_2 as i64,
+        i64::try_from(score_1).map_err(|_| crate::Error {
+            message: format!("total score {} overflows", score_1),
+        })?,
+        i64::try_from(score_2).map_err(|_| crate::Error {
+            message: format!("middle completion score {} overflows", score_2),
+        })?,
         Timing::new(time_setup, time_part_1, time_part_2, time_combined),
     ))
 }
@@ -1,111 +1,326 @@
 //! Day 11:
-//! This solution relies upon knowledge of the grid size for performance.
-//! Without this knowledge, the grids need to be stored in Vecs instead
-//! of arrays. I used a recursive update around each point that triggered
-//! a flash. Most of the speedup came from homogonizing loops and
-//! reducing branching logic.
+//! This solution keeps the octopuses in a padded grid so every cell has 8
+//! neighbours, with the halo energy starting above the flash threshold so the
+//! edges never flash. Flashes propagate through an explicit stack instead of
+//! recursion, so large grids can't overflow the call stack. The grid size is
+//! only known at runtime, but the common sizes are dispatched to const generic
+//! versions of the simulation so the loop bounds are constants, which is most
+//! of the benefit of the old fixed size arrays. The neighbours come from the
+//! grid, which measured just as fast as constant offsets.
+//! The generations can also be walked one at a time to watch the flashes
+//! cascade and synchronize.
+//! Not every grid synchronizes, so the search compares the energies against a
+//! saved copy, moved forward at powers of two as in Brent's cycle detection.
+//! Once the energies repeat without a synchronized flash they never will,
+//! and the search gives up instead of running forever.
 
+use crate::cli::Options;
+use crate::grid::Grid;
 use crate::prelude::*;
 
-const WIDTH: usize = 12;
+pub(crate) const OPTIONS: &[&str] = &["animate", "delay", "generations", "csv"];
+const DEFAULT_DELAY_MS: u64 = 100;
 const FIRST_GENERATIONS: usize = 100;
-const OFFSETS: [i16; 8] = [
-    0 - WIDTH as i16,
-    0 + WIDTH as i16,
-    1,
-    1 - WIDTH as i16,
-    1 + WIDTH as i16,
-    -1,
-    -1 - WIDTH as i16,
-    -1 + WIDTH as i16,
-];
-
-// -----------------------------------------------------------------------------
-// Update energy state
-// -----------------------------------------------------------------------------
-fn update(
-    energy: &mut [u8; WIDTH * WIDTH],
-    visits: &mut [u16; WIDTH * WIDTH],
-    index: usize,
-    current_visit: u16,
-) -> u16 {
-    visits[index] = current_visit;
-
-    if energy[index] == 10 {
-        OFFSETS.iter().for_each(|offset| {
-            visits[index] += 1;
-            energy[(index as i16 + offset) as usize] += 1;
-            visits[index] = update(
-                energy,
-                visits,
-                (index as i16 + offset) as usize,
-                visits[index],
-            );
-        });
+const HALO_ENERGY: u8 = 11;
+const DYNAMIC: usize = 0;
+
+// -----------------------------------------------------------------------------
+// Octopuses data struct
+// -----------------------------------------------------------------------------
+// `WIDTH` and `HEIGHT` of `DYNAMIC` use the grid dimensions instead
+struct Octopuses<const WIDTH: usize, const HEIGHT: usize> {
+    energy: Grid<u8>,
+    flashes: Vec<usize>,
+    stack: Vec<usize>,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Octopuses<WIDTH, HEIGHT> {
+    fn new(energy: Grid<u8>) -> Self {
+        Self {
+            energy,
+            flashes: Vec::with_capacity(128),
+            stack: Vec::with_capacity(128),
+        }
+    }
+
+    fn width(&self) -> usize {
+        if WIDTH == DYNAMIC {
+            self.energy.width()
+        } else {
+            WIDTH
+        }
     }
 
-    visits[index]
+    fn height(&self) -> usize {
+        if HEIGHT == DYNAMIC {
+            self.energy.height()
+        } else {
+            HEIGHT
+        }
+    }
+
+    // Step one generation, returning the flash count
+    fn step(&mut self) -> usize {
+        let (width, height) = (self.width(), self.height());
+        let stride = width + 2;
+        let Self {
+            energy,
+            flashes,
+            stack,
+        } = self;
+
+        // Increase energy
+        flashes.clear();
+        (1..height + 1).for_each(|j| {
+            (1..width + 1).for_each(|i| {
+                let index = i + j * stride;
+                energy[index] += 1;
+                if energy[index] == 10 {
+                    stack.push(index);
+                }
+            })
+        });
+
+        // Propagate flashes
+        while let Some(index) = stack.pop() {
+            flashes.push(index);
+            energy.neighbours_8(index).for_each(|neighbour| {
+                energy[neighbour] += 1;
+                if energy[neighbour] == 10 {
+                    stack.push(neighbour);
+                }
+            });
+        }
+
+        // Reset flashed octopuses and the halo
+        flashes.iter().for_each(|index| energy[*index] = 0);
+        energy.reset_halo();
+        flashes.len()
+    }
 }
 
 // -----------------------------------------------------------------------------
-// Count flashes
+// Cycle data struct
 // -----------------------------------------------------------------------------
-fn count_flashes(energy: &mut [u8; WIDTH * WIDTH]) -> i32 {
-    let flash_count = (1..WIDTH - 1).fold(0, |acc, j| {
-        acc + (1..WIDTH - 1).fold(0, |acc, i| {
-            if energy[i + j * WIDTH] > 9 {
-                energy[i + j * WIDTH] = 0;
-                acc + 1
-            } else {
-                acc
-            }
-        })
-    });
-    flash_count as i32
+// Saved energies, replaced after a power of two generations
+struct Cycle {
+    saved: Vec<u8>,
+    power: usize,
+    length: usize,
+}
+
+impl Cycle {
+    fn new(energy: &Grid<u8>) -> Self {
+        Self {
+            saved: energy.cells().to_vec(),
+            power: 1,
+            length: 0,
+        }
+    }
+
+    // Whether the energies repeat a state seen before
+    fn repeats(&mut self, energy: &Grid<u8>) -> bool {
+        if energy.cells() == &self.saved[..] {
+            return true;
+        }
+        self.length += 1;
+        if self.length == self.power {
+            self.saved.copy_from_slice(energy.cells());
+            self.power *= 2;
+            self.length = 0;
+        }
+        false
+    }
 }
 
 // -----------------------------------------------------------------------------
 // Part 1
 // -----------------------------------------------------------------------------
-fn part_1(
-    energy: &mut [u8; WIDTH * WIDTH],
-    visits: &mut [u16; WIDTH * WIDTH],
-) -> crate::Result<i32> {
-    let mut current_visit = 1;
-    let mut flash_count = 0;
-    (0..FIRST_GENERATIONS).for_each(|_| {
-        (1..WIDTH - 1).for_each(|j| {
-            (1..WIDTH - 1).for_each(|i| {
-                energy[i + j * WIDTH] += 1;
-                current_visit = update(energy, visits, i + j * WIDTH, current_visit + 1);
-            })
-        });
-        flash_count += count_flashes(energy);
+// Total flashes, and the first synchronized generation if there was one
+fn part_1<const WIDTH: usize, const HEIGHT: usize>(
+    octopuses: &mut Octopuses<WIDTH, HEIGHT>,
+) -> crate::Result<(usize, Option<usize>)> {
+    let size = octopuses.width() * octopuses.height();
+    let mut synchronized = None;
+    let flash_count = (1..FIRST_GENERATIONS + 1).fold(0, |flash_count, generation| {
+        let flashes = octopuses.step();
+        if flashes == size && synchronized.is_none() {
+            synchronized = Some(generation);
+        }
+        flash_count + flashes
     });
-    Ok(flash_count as i32)
+    Ok((flash_count, synchronized))
 }
 
 // -----------------------------------------------------------------------------
 // Part 2
 // -----------------------------------------------------------------------------
-fn part_2(
-    energy: &mut [u8; WIDTH * WIDTH],
-    visits: &mut [u16; WIDTH * WIDTH],
-    mut current_visit: u16,
-) -> crate::Result<i32> {
+fn part_2<const WIDTH: usize, const HEIGHT: usize>(
+    octopuses: &mut Octopuses<WIDTH, HEIGHT>,
+    synchronized: Option<usize>,
+) -> crate::Result<usize> {
+    if let Some(generation) = synchronized {
+        return Ok(generation);
+    }
+    let size = octopuses.width() * octopuses.height();
+    let mut cycle = Cycle::new(&octopuses.energy);
     let mut generation = FIRST_GENERATIONS;
-    let mut all_flashed = false;
-    while !all_flashed {
+    loop {
         generation += 1;
-        (1..WIDTH - 1).for_each(|j| {
-            (1..WIDTH - 1).for_each(|i| {
-                energy[i + j * WIDTH] += 1;
-                current_visit = update(energy, visits, i + j * WIDTH, current_visit + 1);
+        if octopuses.step() == size {
+            return Ok(generation);
+        }
+        if cycle.repeats(&octopuses.energy) {
+            return Err(crate::Error {
+                message: format!(
+                    "octopuses never synchronize, energies repeat by generation {}",
+                    generation
+                ),
+            });
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Generation data struct
+// -----------------------------------------------------------------------------
+pub(crate) struct Generation {
+    pub(crate) number: usize,
+    pub(crate) energy: Grid<u8>,
+    pub(crate) flashed: Vec<(usize, usize)>,
+    // Whether the energies repeat an earlier generation
+    pub(crate) repeated: bool,
+}
+
+impl Generation {
+    pub(crate) fn all_flashed(&self) -> bool {
+        self.flashed.len() == self.energy.width() * self.energy.height()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Generations iterator
+// -----------------------------------------------------------------------------
+pub(crate) struct Generations {
+    octopuses: Octopuses<DYNAMIC, DYNAMIC>,
+    cycle: Cycle,
+    number: usize,
+}
+
+impl Iterator for Generations {
+    type Item = Generation;
+    fn next(&mut self) -> Option<Generation> {
+        self.octopuses.step();
+        self.number += 1;
+        let energy = &self.octopuses.energy;
+        let mut flashed: Vec<(usize, usize)> = self
+            .octopuses
+            .flashes
+            .iter()
+            .map(|index| energy.coordinates(*index))
+            .collect();
+        flashed.sort_unstable_by_key(|(x, y)| (*y, *x));
+        Some(Generation {
+            number: self.number,
+            energy: energy.clone(),
+            flashed,
+            repeated: self.cycle.repeats(energy),
+        })
+    }
+}
+
+pub(crate) fn generations(buffer: &str) -> crate::Result<Generations> {
+    let energy = Grid::from_digits(buffer, HALO_ENERGY)?;
+    Ok(Generations {
+        cycle: Cycle::new(&energy),
+        octopuses: Octopuses::new(energy),
+        number: 0,
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Animation
+// -----------------------------------------------------------------------------
+fn draw(generation: &Generation, total_flashes: usize) {
+    // Move the cursor home and clear the screen before redrawing
+    print!("\x1b[H\x1b[2J");
+    println!(
+        "    {}: {}  {}: {}  {}: {}",
+        "Generation".purple().bold(),
+        generation.number,
+        "Flashes".purple().bold(),
+        generation.flashed.len(),
+        "Total".purple().bold(),
+        total_flashes
+    );
+    generation.energy.rows().for_each(|row| {
+        let row: String = row
+            .iter()
+            .map(|energy| match energy {
+                0 => "█".yellow().bold().to_string(),
+                1..=3 => "░".blue().to_string(),
+                4..=6 => "▒".blue().to_string(),
+                _ => "▓".blue().to_string(),
             })
-        });
-        all_flashed = count_flashes(energy) as usize == (WIDTH - 2) * (WIDTH - 2);
+            .collect();
+        println!("    {}", row);
+    });
+}
+
+// -----------------------------------------------------------------------------
+// Extras
+// -----------------------------------------------------------------------------
+pub(crate) fn extras(buffer: &str, options: &Options) -> crate::Result<()> {
+    let animate = options.flag("animate");
+    let csv = options.value("csv");
+    if !animate && csv.is_none() {
+        return Ok(());
+    }
+    let delay =
+        std::time::Duration::from_millis(options.parse("delay")?.unwrap_or(DEFAULT_DELAY_MS));
+    let limit: Option<usize> = options.parse("generations")?;
+
+    // Run to the requested generation, or until the octopuses synchronize or
+    // the energies repeat
+    let mut total_flashes = 0;
+    let mut rows = vec![];
+    let mut repeated = None;
+    for generation in generations(buffer)? {
+        total_flashes += generation.flashed.len();
+        if animate {
+            draw(&generation, total_flashes);
+            std::thread::sleep(delay);
+        }
+        rows.push(vec![
+            generation.number.to_string(),
+            generation.flashed.len().to_string(),
+            total_flashes.to_string(),
+        ]);
+        let done = match limit {
+            Some(limit) => generation.number >= limit,
+            None if generation.repeated && !generation.all_flashed() => {
+                repeated = Some(generation.number);
+                true
+            }
+            None => generation.all_flashed(),
+        };
+        if done {
+            break;
+        }
     }
-    Ok(generation as i32)
+    println!("    {}:", "Flashes".purple().bold());
+    println!("      Generations: {}", rows.len());
+    println!("      Total: {}", total_flashes);
+    if let Some(number) = repeated {
+        println!(
+            "      Never synchronizes, energies repeat by generation {}",
+            number
+        );
+    }
+    if let Some(path) = csv {
+        output::write_csv(path, &["generation", "flashes", "total_flashes"], &rows)?;
+    }
+    Ok(())
 }
 
 // -----------------------------------------------------------------------------
@@ -115,16 +330,20 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Setup
     // -------------------------------------------------------------------------
-    // Read to vector
+    // Read to grid
     let start_setup = Instant::now();
-    let mut energy: [u8; WIDTH * WIDTH] = [11; WIDTH * WIDTH];
-    let mut visits: [u16; WIDTH * WIDTH] = [0; WIDTH * WIDTH];
-    buffer.lines().enumerate().for_each(|(j, line)| {
-        line.as_bytes().iter().enumerate().for_each(|(i, b)| {
-            energy[(i + 1) + (j + 1) * WIDTH] = *b - b'0';
-            visits[(i + 1) + (j + 1) * WIDTH] = 1;
-        })
-    });
+    let energy = Grid::from_digits(&buffer, HALO_ENERGY)?;
+    match (energy.width(), energy.height()) {
+        (10, 10) => run_sized(Octopuses::<10, 10>::new(energy), start_setup),
+        (100, 100) => run_sized(Octopuses::<100, 100>::new(energy), start_setup),
+        _ => run_sized(Octopuses::<DYNAMIC, DYNAMIC>::new(energy), start_setup),
+    }
+}
+
+fn run_sized<const WIDTH: usize, const HEIGHT: usize>(
+    mut octopuses: Octopuses<WIDTH, HEIGHT>,
+    start_setup: Instant,
+) -> crate::Result<RunData> {
     let time_setup = start_setup.elapsed();
 
     // -------------------------------------------------------------------------
@@ -132,7 +351,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Run 100 generations
     let start_part_1 = Instant::now();
-    let count_1 = part_1(&mut energy, &mut visits)?;
+    let (count_1, synchronized) = part_1(&mut octopuses)?;
     let time_part_1 = start_part_1.elapsed();
 
     // -------------------------------------------------------------------------
@@ -140,7 +359,7 @@ pub(crate) fn run(buffer: String) -> crate::Result<RunData> {
     // -------------------------------------------------------------------------
     // Look for syncronization
     let start_part_2 = Instant::now();
-    let generation_2 = part_2(&mut energy, &mut visits, count_1 as u16 * 10)?;
+    let generation_2 = part_2(&mut octopuses, synchronized)?;
     let time_part_2 = start_part_2.elapsed();
 
     // -------------------------------------------------------------------------
@@ -0,0 +1,204 @@
+//! Fuzz:
+//! This module has a fuzz target for every day and a small mutation based
+//! fuzzer to drive them, so it runs offline as part of the tests. Each target
+//! feeds one input through the parser and solver of its day, which may reject
+//! the input but may never panic. The days are free of `unsafe`, so a panic
+//! is the only way for malformed input to go wrong. Integer overflow only
+//! panics with overflow checks on, so the fuzzing that counts runs in a debug
+//! build, and CI does both. The checked-in corpus in `fuzz/corpus/dayNN` seeds
+//! the mutations, and setting `FUZZ_ITERATIONS` runs longer campaigns than the
+//! default.
+
+use crate::testing::Rng;
+
+const CORPUS: &str = "fuzz/corpus";
+const DEFAULT_ITERATIONS: usize = 300;
+
+// Fragments that mean something to at least one parser
+const DICTIONARY: &[&str] = &[
+    "\n", "\n\n", " ", ",", "->", " -> ", "|", " | ", "0", "1", "9", "forward ", "down ", "up ",
+    "repeat ", "end", "(", ")", "[", "]", "{", "}", "<", ">", "«", "»", "abcdefg", "-",
+];
+
+// Numbers at the edges of the integer types
+const NUMBERS: &[&str] = &[
+    "0",
+    "1",
+    "255",
+    "256",
+    "65535",
+    "65536",
+    "2147483647",
+    "4294967295",
+    "4294967296",
+    "9223372036854775807",
+    "18446744073709551615",
+    "340282366920938463463374607431768211455",
+    "99999999999999999999999999999999999999999",
+];
+
+// -----------------------------------------------------------------------------
+// Targets
+// -----------------------------------------------------------------------------
+pub(crate) type Target = fn(&str);
+
+fn day01(input: &str) {
+    let _ = crate::day01::run(input.to_string());
+}
+
+fn day02(input: &str) {
+    let _ = crate::day02::run(input.to_string());
+}
+
+fn day03(input: &str) {
+    let _ = crate::day03::run(input.to_string());
+}
+
+fn day04(input: &str) {
+    let _ = crate::day04::run(input.to_string());
+}
+
+fn day05(input: &str) {
+    let _ = crate::day05::run(input.to_string());
+}
+
+fn day06(input: &str) {
+    let _ = crate::day06::run(input.to_string());
+}
+
+fn day07(input: &str) {
+    let _ = crate::day07::run(input.to_string());
+}
+
+fn day08(input: &str) {
+    let _ = crate::day08::run(input.to_string());
+}
+
+fn day09(input: &str) {
+    let _ = crate::day09::run(input.to_string());
+}
+
+fn day10(input: &str) {
+    let _ = crate::day10::run(input.to_string());
+}
+
+fn day11(input: &str) {
+    let _ = crate::day11::run(input.to_string());
+}
+
+pub(crate) const TARGETS: [(&str, Target); 11] = [
+    ("day01", day01),
+    ("day02", day02),
+    ("day03", day03),
+    ("day04", day04),
+    ("day05", day05),
+    ("day06", day06),
+    ("day07", day07),
+    ("day08", day08),
+    ("day09", day09),
+    ("day10", day10),
+    ("day11", day11),
+];
+
+// -----------------------------------------------------------------------------
+// Mutations
+// -----------------------------------------------------------------------------
+fn mutate(input: &str, corpus: &[String], rng: &mut Rng) -> String {
+    let mut bytes = input.as_bytes().to_vec();
+    for _ in 0..rng.range(1, 4) {
+        let position = rng.range(0, bytes.len() as u64) as usize;
+        let length = rng.range(0, 8.min(bytes.len() - position) as u64) as usize;
+        match rng.range(0, 6) {
+            // Overwrite a byte with anything
+            0 if position < bytes.len() => bytes[position] = rng.range(0, 255) as u8,
+            // Drop a few bytes
+            1 => {
+                bytes.drain(position..position + length);
+            }
+            // Repeat a few bytes
+            2 => {
+                let copy = bytes[position..position + length].to_vec();
+                bytes.splice(position..position, copy);
+            }
+            // Insert a known fragment
+            3 => {
+                let fragment = DICTIONARY[rng.range(0, DICTIONARY.len() as u64 - 1) as usize];
+                bytes.splice(position..position, fragment.bytes());
+            }
+            // Swap a number for an extreme one
+            4 => {
+                let start = bytes[..position]
+                    .iter()
+                    .rposition(|b| !b.is_ascii_digit())
+                    .map_or(0, |i| i + 1);
+                let stop = bytes[position..]
+                    .iter()
+                    .position(|b| !b.is_ascii_digit())
+                    .map_or(bytes.len(), |i| position + i);
+                let number = NUMBERS[rng.range(0, NUMBERS.len() as u64 - 1) as usize];
+                bytes.splice(start..stop, number.bytes());
+            }
+            // Splice in part of another input
+            5 => {
+                let other = corpus[rng.range(0, corpus.len() as u64 - 1) as usize].as_bytes();
+                let start = rng.range(0, other.len() as u64) as usize;
+                let stop = rng.range(start as u64, other.len() as u64) as usize;
+                bytes.truncate(position);
+                bytes.extend_from_slice(&other[start..stop]);
+            }
+            _ => bytes.truncate(position),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// -----------------------------------------------------------------------------
+// Harness
+// -----------------------------------------------------------------------------
+pub(crate) fn load_corpus(name: &str) -> crate::Result<Vec<String>> {
+    let mut paths: Vec<_> = std::fs::read_dir(format!("{}/{}", CORPUS, name))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.sort();
+    let corpus = paths
+        .iter()
+        .map(|path| crate::load::data_to_buffer(path.display().to_string()))
+        .collect::<crate::Result<Vec<String>>>()?;
+    if corpus.is_empty() {
+        return Err(crate::Error {
+            message: format!("empty corpus for {}", name),
+        });
+    }
+    Ok(corpus)
+}
+
+// Inputs that made the target panic
+pub(crate) fn fuzz(target: Target, corpus: &[String], iterations: usize, seed: u64) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let mut crashes = vec![];
+    let mut input = corpus[0].clone();
+    for i in 0..corpus.len() + iterations {
+        input = match corpus.get(i) {
+            Some(seed) => seed.clone(),
+            // Mostly keep building on the last input, now and then start over
+            None if rng.one_in(4) || input.len() > 10_000 => {
+                let start = &corpus[rng.range(0, corpus.len() as u64 - 1) as usize];
+                mutate(start, corpus, &mut rng)
+            }
+            None => mutate(&input, corpus, &mut rng),
+        };
+        if std::panic::catch_unwind(|| target(&input)).is_err() {
+            crashes.push(input.clone());
+        }
+    }
+    crashes
+}
+
+pub(crate) fn iterations() -> usize {
+    std::env::var("FUZZ_ITERATIONS")
+        .ok()
+        .and_then(|iterations| iterations.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS)
+}
+
+// -----------------------------------------------------------------------------
@@ -0,0 +1,228 @@
+//! Grid:
+//! This module has a padded 2D grid shared by the map based days. The interior
+//! is surrounded by a one cell halo holding a fixed value, so neighbour lookups
+//! never need special logic for the edges and corners. Hot loops work with the
+//! flat padded index and the neighbour offsets directly.
+
+use std::ops::{Index, IndexMut};
+
+// -----------------------------------------------------------------------------
+// Neighbour offsets for a given stride, applied with `wrapping_add`
+// -----------------------------------------------------------------------------
+pub(crate) const fn offsets_4(stride: usize) -> [usize; 4] {
+    [
+        1,
+        stride,
+        0_usize.wrapping_sub(1),
+        0_usize.wrapping_sub(stride),
+    ]
+}
+
+pub(crate) const fn offsets_8(stride: usize) -> [usize; 8] {
+    [
+        1,
+        stride + 1,
+        stride,
+        stride - 1,
+        0_usize.wrapping_sub(1),
+        0_usize.wrapping_sub(stride + 1),
+        0_usize.wrapping_sub(stride),
+        0_usize.wrapping_sub(stride - 1),
+    ]
+}
+
+// -----------------------------------------------------------------------------
+// Grid data struct
+// -----------------------------------------------------------------------------
+#[derive(Clone, Debug)]
+pub(crate) struct Grid<T> {
+    width: usize,
+    height: usize,
+    halo: T,
+    data: Vec<T>,
+}
+
+impl<T: Copy> Grid<T> {
+    pub(crate) fn new(width: usize, height: usize, halo: T, fill: T) -> Self {
+        let stride = width + 2;
+        let mut data = vec![halo; stride * (height + 2)];
+        (1..=height).for_each(|j| {
+            data[1 + j * stride..=width + j * stride]
+                .iter_mut()
+                .for_each(|cell| *cell = fill)
+        });
+        Self {
+            width,
+            height,
+            halo,
+            data,
+        }
+    }
+
+    // Flat padded storage, for hot loops
+    pub(crate) fn cells(&self) -> &[T] {
+        &self.data
+    }
+
+    pub(crate) fn width(&self) -> usize {
+        self.width
+    }
+
+    pub(crate) fn height(&self) -> usize {
+        self.height
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn halo(&self) -> T {
+        self.halo
+    }
+
+    // Distance between vertically adjacent cells in the padded storage
+    pub(crate) fn stride(&self) -> usize {
+        self.width + 2
+    }
+
+    // Padded index of interior coordinate (x, y), both starting at 0
+    pub(crate) fn index(&self, x: usize, y: usize) -> usize {
+        (x + 1) + (y + 1) * self.stride()
+    }
+
+    // Interior coordinate (x, y) of a padded index
+    pub(crate) fn coordinates(&self, index: usize) -> (usize, usize) {
+        (index % self.stride() - 1, index / self.stride() - 1)
+    }
+
+    pub(crate) fn get(&self, x: usize, y: usize) -> T {
+        self.data[self.index(x, y)]
+    }
+
+    // Padded indices of the interior, row by row
+    pub(crate) fn interior_indices(&self) -> impl Iterator<Item = usize> {
+        let stride = self.stride();
+        let width = self.width;
+        (1..=self.height).flat_map(move |j| (1..=width).map(move |i| i + j * stride))
+    }
+
+    pub(crate) fn row(&self, y: usize) -> &[T] {
+        let start = self.index(0, y);
+        &self.data[start..start + self.width]
+    }
+
+    pub(crate) fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.data[self.index(x, 0)..]
+            .iter()
+            .step_by(self.stride())
+            .take(self.height)
+    }
+
+    // Offsets to the 4 edge neighbours, applied with `wrapping_add`
+    pub(crate) fn offsets_4(&self) -> [usize; 4] {
+        offsets_4(self.stride())
+    }
+
+    // Offsets to the 8 edge and corner neighbours, applied with `wrapping_add`
+    pub(crate) fn offsets_8(&self) -> [usize; 8] {
+        offsets_8(self.stride())
+    }
+
+    // Restore the halo value, for simulations that spill into the halo
+    pub(crate) fn reset_halo(&mut self) {
+        let stride = self.stride();
+        let last_row = self.data.len() - stride;
+        let halo = self.halo;
+        self.data[..stride].fill(halo);
+        self.data[last_row..].fill(halo);
+        (1..self.height + 1).for_each(|j| {
+            self.data[j * stride] = halo;
+            self.data[j * stride + stride - 1] = halo;
+        });
+    }
+
+    // Padded indices of the 4 edge neighbours
+    pub(crate) fn neighbours_4(&self, index: usize) -> impl Iterator<Item = usize> {
+        IntoIterator::into_iter(self.offsets_4()).map(move |offset| index.wrapping_add(offset))
+    }
+
+    // Padded indices of the 8 edge and corner neighbours
+    pub(crate) fn neighbours_8(&self, index: usize) -> impl Iterator<Item = usize> {
+        IntoIterator::into_iter(self.offsets_8()).map(move |offset| index.wrapping_add(offset))
+    }
+}
+
+impl<T: Copy + From<u8>> Grid<T> {
+    // Parse rows of digit characters, such as `2199943210`
+    pub(crate) fn from_digits(buffer: &str, halo: T) -> crate::Result<Self> {
+        let mut lines = buffer.lines().map(|line| line.trim_end().as_bytes());
+        let mut line = lines.next().unwrap_or_default();
+        let width = line.len();
+        if width == 0 {
+            return Err(crate::Error {
+                message: "empty grid".to_string(),
+            });
+        }
+        // Build the padded storage one row at a time
+        let mut data = Vec::with_capacity((width + 2) * (buffer.len() / (width + 1) + 3));
+        data.resize(width + 2, halo);
+        let mut height = 0;
+        while !line.is_empty() {
+            height += 1;
+            if line.len() != width {
+                return Err(crate::Error {
+                    message: format!(
+                        "row {} has {} columns, expected {}",
+                        height,
+                        line.len(),
+                        width
+                    ),
+                });
+            }
+            let mut invalid = false;
+            data.push(halo);
+            data.extend(line.iter().map(|b| {
+                let digit = b.wrapping_sub(b'0');
+                invalid |= digit > 9;
+                T::from(digit)
+            }));
+            data.push(halo);
+            if invalid {
+                let b = line.iter().find(|b| !b.is_ascii_digit()).unwrap_or(&b'?');
+                return Err(crate::Error {
+                    message: format!("invalid digit '{}' in row {}", *b as char, height),
+                });
+            }
+            line = lines.next().unwrap_or_default();
+        }
+        if lines.any(|line| !line.is_empty()) {
+            return Err(crate::Error {
+                message: format!("blank line after row {}", height),
+            });
+        }
+        data.resize(data.len() + width + 2, halo);
+        Ok(Self {
+            width,
+            height,
+            halo,
+            data,
+        })
+    }
+}
+
+impl<T> Index<usize> for Grid<T> {
+    type Output = T;
+    fn index(&self, index: usize) -> &T {
+        &self.data[index]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.data[index]
+    }
+}
+
+// -----------------------------------------------------------------------------
@@ -1,6 +1,8 @@
 // -----------------------------------------------------------------------------
 // Modules
 // -----------------------------------------------------------------------------
+mod bignum;
+mod cli;
 mod day01;
 mod day02;
 mod day03;
@@ -12,8 +14,14 @@ mod day08;
 mod day09;
 mod day10;
 mod day11;
+#[cfg(test)]
+mod fuzz;
+mod grid;
 mod load;
 mod output;
+mod remote;
+#[cfg(test)]
+mod testing;
 
 use crate::prelude::*;
 
@@ -57,6 +65,14 @@ impl From<std::string::FromUtf8Error> for Error {
     }
 }
 
+impl From<ureq::Error> for Error {
+    fn from(ureq_error: ureq::Error) -> Self {
+        Self {
+            message: ureq_error.to_string(),
+        }
+    }
+}
+
 // -----------------------------------------------------------------------------
 // RunData struct
 // -----------------------------------------------------------------------------
@@ -64,6 +80,8 @@ impl From<std::string::FromUtf8Error> for Error {
 pub(crate) struct RunData {
     part_1: i64,
     part_2: i64,
+    // Second value for each part, such as where the answer was found
+    details: Option<(i64, i64)>,
     times: Timing,
 }
 
@@ -72,9 +90,17 @@ impl RunData {
         Self {
             part_1,
             part_2,
+            details: None,
             times,
         }
     }
+
+    fn with_details(self, part_1: i64, part_2: i64) -> Self {
+        Self {
+            details: Some((part_1, part_2)),
+            ..self
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -120,48 +146,141 @@ pub(crate) mod prelude {
 // Main Driver
 // -----------------------------------------------------------------------------
 fn main() -> Result<()> {
-    // Setup
-    const DAYS: usize = 11;
-    let runs = [
-        day01::run,
-        day02::run,
-        day03::run,
-        day04::run,
-        day05::run,
-        day06::run,
-        day07::run,
-        day08::run,
-        day09::run,
-        day10::run,
-        day11::run,
-    ];
-    let data = [
-        "data/day01_actual.txt",
-        "data/day02_actual.txt",
-        "data/day03_actual.txt",
-        "data/day04_actual.txt",
-        "data/day05_actual.txt",
-        "data/day06_actual.txt",
-        "data/day07_actual.txt",
-        "data/day08_actual.txt",
-        "data/day09_actual.txt",
-        "data/day10_actual.txt",
-        "data/day11_actual.txt",
-    ];
-    let reports = [
-        day01::report,
-        day02::report,
-        day03::report,
-        day04::report,
-        day05::report,
-        day06::report,
-        day07::report,
-        day08::report,
-        day09::report,
-        day10::report,
-        day11::report,
-    ];
+    match cli::parse(std::env::args().skip(1))? {
+        cli::Command::Run => run_all(),
+        cli::Command::Day {
+            day,
+            input,
+            options,
+        } => run_day(day, input, options),
+        cli::Command::Fetch { days, force } => fetch(days, force),
+        cli::Command::Submit { day, part, answer } => submit(day, part, &answer),
+    }
+}
 
+// -----------------------------------------------------------------------------
+// Fetch input
+// -----------------------------------------------------------------------------
+fn fetch(mut days: Vec<usize>, force: bool) -> Result<()> {
+    let mut client = remote::Client::new(remote::Config::load()?);
+    if days.is_empty() {
+        days = (1..=DAYS).collect();
+    }
+    for day in days {
+        let status = if client.fetch_input(day, force)? {
+            "downloaded".green()
+        } else {
+            "cached".blue()
+        };
+        println!("Day {:02}: {} {}", day, client.input_path(day), status);
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Submit answer
+// -----------------------------------------------------------------------------
+fn submit(day: usize, part: usize, answer: &str) -> Result<()> {
+    let mut client = remote::Client::new(remote::Config::load()?);
+    let submission = client.submit(day, part, answer)?;
+    println!("Day {:02} Part {}: {} is {}", day, part, answer, submission);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Days
+// -----------------------------------------------------------------------------
+const DAYS: usize = 11;
+type Run = fn(String) -> Result<RunData>;
+type Report = fn(&RunData) -> Result<()>;
+const RUNS: [Run; DAYS] = [
+    day01::run,
+    day02::run,
+    day03::run,
+    day04::run,
+    day05::run,
+    day06::run,
+    day07::run,
+    day08::run,
+    day09::run,
+    day10::run,
+    day11::run,
+];
+const DATA: [&str; DAYS] = [
+    "data/day01_actual.txt",
+    "data/day02_actual.txt",
+    "data/day03_actual.txt",
+    "data/day04_actual.txt",
+    "data/day05_actual.txt",
+    "data/day06_actual.txt",
+    "data/day07_actual.txt",
+    "data/day08_actual.txt",
+    "data/day09_actual.txt",
+    "data/day10_actual.txt",
+    "data/day11_actual.txt",
+];
+const REPORTS: [Report; DAYS] = [
+    day01::report,
+    day02::report,
+    day03::report,
+    day04::report,
+    day05::report,
+    day06::report,
+    day07::report,
+    day08::report,
+    day09::report,
+    day10::report,
+    day11::report,
+];
+
+// -----------------------------------------------------------------------------
+// Run one day
+// -----------------------------------------------------------------------------
+fn run_day(day: usize, input: Option<String>, options: cli::Options) -> Result<()> {
+    if day > DAYS {
+        return Err(Error {
+            message: format!("Day {} is not solved yet", day),
+        });
+    }
+    let known_options = match day {
+        1 => day01::OPTIONS,
+        2 => day02::OPTIONS,
+        4 => day04::OPTIONS,
+        5 => day05::OPTIONS,
+        6 => day06::OPTIONS,
+        7 => day07::OPTIONS,
+        8 => day08::OPTIONS,
+        9 => day09::OPTIONS,
+        10 => day10::OPTIONS,
+        11 => day11::OPTIONS,
+        _ => &[],
+    };
+    options.check(known_options)?;
+    let buffer = crate::load::data_to_buffer(input.unwrap_or_else(|| DATA[day - 1].to_string()))?;
+
+    output::print_header()?;
+    REPORTS[day - 1](&RUNS[day - 1](buffer.clone())?)?;
+    match day {
+        1 => day01::extras(&buffer, &options)?,
+        2 => day02::extras(&buffer, &options)?,
+        4 => day04::extras(&buffer, &options)?,
+        5 => day05::extras(&buffer, &options)?,
+        6 => day06::extras(&buffer, &options)?,
+        7 => day07::extras(&buffer, &options)?,
+        8 => day08::extras(&buffer, &options)?,
+        9 => day09::extras(&buffer, &options)?,
+        10 => day10::extras(&buffer, &options)?,
+        11 => day11::extras(&buffer, &options)?,
+        _ => (),
+    }
+    output::print_header()?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Run all days
+// -----------------------------------------------------------------------------
+fn run_all() -> Result<()> {
     // Each day
     output::print_header()?;
     let mut day_results: [Vec<RunData>; DAYS] = [
@@ -178,8 +297,8 @@ fn main() -> Result<()> {
         vec![],
     ];
     for _ in 0..REPETITIONS {
-        for (i, day) in runs.iter().enumerate() {
-            let buffer = crate::load::data_to_buffer(data[i].to_string())?;
+        for (i, day) in RUNS.iter().enumerate() {
+            let buffer = crate::load::data_to_buffer(DATA[i].to_string())?;
             day_results[i].push(day(buffer)?);
         }
     }
@@ -209,7 +328,7 @@ fn main() -> Result<()> {
             message: format!("Day {} data not found", i),
         })?;
         let timing = &average_times[i];
-        reports[i](&RunData::new(
+        REPORTS[i](&RunData::new(
             result.part_1,
             result.part_2,
             Timing::new(timing.setup, timing.part_1, timing.part_2, timing.combined),
@@ -286,6 +405,57 @@ mod tests {
         Ok(())
     }
 
+    // Sum every window and compare neighbouring sums directly
+    fn naive_increases(depths: &[i32], window: usize) -> usize {
+        let sums: Vec<i32> = depths.windows(window).map(|w| w.iter().sum()).collect();
+        sums.windows(2).filter(|pair| pair[1] > pair[0]).count()
+    }
+
+    #[test]
+    fn test_01_profile() -> Result<()> {
+        let depths = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+        let profile = day01::analyze(&depths, 3)?;
+        assert_eq!(profile.longest_run, (0, 3));
+        assert_eq!(profile.largest_jump, (5, 33));
+        assert_eq!(profile.moving_average.len(), 8);
+        assert_eq!(profile.moving_average[1], 206.0);
+        assert_eq!(output::sparkline(&profile.moving_average, 8), "▁▁▁▁▃▅▇█");
+        assert_eq!(
+            output::sparkline(&profile.moving_average, 4)
+                .chars()
+                .count(),
+            4
+        );
+        assert!(day01::analyze(&depths[..1], 3).is_err());
+
+        // Depths near the ends of i32 jump and average without overflowing
+        let depths = [i32::MIN, i32::MAX, i32::MAX];
+        let profile = day01::analyze(&depths, 2)?;
+        assert_eq!(profile.largest_jump, (0, u32::MAX as i64));
+        assert_eq!(profile.moving_average[1], i32::MAX as f64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_01_windows() -> Result<()> {
+        for file in ["data/day01_sample.txt", "data/day01_actual.txt"] {
+            let buffer = crate::load::data_to_buffer(file.to_string())?;
+            let depths: Vec<i32> = buffer
+                .lines()
+                .map(|line| line.parse())
+                .collect::<std::result::Result<_, _>>()?;
+            for window in 1..=10 {
+                assert_eq!(
+                    day01::count_increases(depths.iter().copied(), window)?,
+                    naive_increases(&depths, window)
+                );
+            }
+        }
+        assert_eq!(day01::count_increases(vec![1, 2], 5)?, 0);
+        assert!(day01::count_increases(vec![1, 2], 0).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_02_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day02_sample.txt".to_string())?;
@@ -302,6 +472,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_02_commands() -> Result<()> {
+        use day02::Submarine;
+        let program = day02::parse_program(
+            "forward 5\nrepeat 2\n  down 3\n  back 1\nend\nreset\nforward 2\ndown 4\n",
+        )?;
+        let pilot: day02::Pilot = day02::pilot(&program)?;
+        assert_eq!((pilot.horizontal(), pilot.depth()), (2, 4));
+        let program = day02::parse_program("down 2\nrepeat 3\nforward 1\nend\nup 1\n")?;
+        let pilot: day02::AimedPilot = day02::pilot(&program)?;
+        assert_eq!((pilot.horizontal(), pilot.depth()), (3, 6));
+        let error = day02::parse_program("forward 5\nsideways 2\n").unwrap_err();
+        assert_eq!(error.message, "line 2: unknown command 'sideways'");
+        assert!(day02::parse_program("forward x\n").is_err());
+        assert!(day02::parse_program("repeat 2\nforward 1\n").is_err());
+        assert!(day02::parse_program("end\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_02_trace() -> Result<()> {
+        let buffer = crate::load::data_to_buffer("data/day02_sample.txt".to_string())?;
+        let program = day02::parse_program(&buffer)?;
+        let states = day02::trace::<day02::AimedPilot>(&program)?;
+        assert_eq!(states.len(), 7);
+        assert_eq!(states[0], day02::State::default());
+        let summary = day02::summarize(&states);
+        assert_eq!(summary.max_depth, 60);
+        assert_eq!((summary.last.horizontal, summary.last.depth), (15, 60));
+        assert_eq!(summary.last.aim, 10);
+        let states = day02::trace::<day02::Pilot>(&program)?;
+        let summary = day02::summarize(&states);
+        assert_eq!(summary.distance, 31.0);
+
+        // A reset from the far end of i32 measures without overflowing
+        let program = day02::parse_program("back 2147483647\nback 1\nreset\n")?;
+        let states = day02::trace::<day02::Pilot>(&program)?;
+        assert_eq!(day02::summarize(&states).distance, 2.0 * 2147483648.0);
+        let plot = output::ascii_plot(&[(0.0, 0.0), (4.0, 2.0)], 5, 3);
+        assert_eq!(plot.len(), 5);
+        assert!(plot[0].ends_with("│•    "));
+        assert!(plot[2].ends_with("│    •"));
+        Ok(())
+    }
+
     #[test]
     fn test_03_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day03_sample.txt".to_string())?;
@@ -318,6 +533,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_03_wide() -> Result<()> {
+        // Compare against counting the characters of each column directly
+        let lines: Vec<String> = (0..301_u128)
+            .map(|i| {
+                let value = i.wrapping_mul(0x9e37_79b9_7f4a_7c15_f39c_c060_5ced_c835);
+                format!("{:0128b}", value)
+            })
+            .collect();
+        let report: day03::Report = lines.join("\n").parse()?;
+        let gamma = (0..128).fold(0_u128, |gamma, column| {
+            let ones = lines
+                .iter()
+                .filter(|line| line.as_bytes()[column] == b'1')
+                .count();
+            (gamma << 1) | (ones > lines.len() / 2) as u128
+        });
+        assert_eq!(day03::power_rates(&report), (gamma, !gamma));
+        let (oxygen, co2) = day03::life_support_ratings(&report);
+        assert!(lines.contains(&format!("{:0128b}", oxygen)));
+        assert!(lines.contains(&format!("{:0128b}", co2)));
+
+        let buffer = crate::load::data_to_buffer("data/day03_sample.txt".to_string())?;
+        let report: day03::Report = buffer.parse()?;
+        assert_eq!(day03::power_rates(&report), (22, 9));
+        assert_eq!(day03::life_support_ratings(&report), (23, 10));
+        assert!("0101\n011\n".parse::<day03::Report>().is_err());
+        assert!("0121\n".parse::<day03::Report>().is_err());
+        assert!("0".repeat(129).parse::<day03::Report>().is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_04_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day04_sample.txt".to_string())?;
@@ -334,6 +581,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_04_rules() -> Result<()> {
+        let buffer = crate::load::data_to_buffer("data/day04_sample.txt".to_string())?;
+        let bingo: day04::Bingo = buffer.parse()?;
+        let rules = day04::Rules::default();
+        assert_eq!("rows,columns".parse::<day04::Rules>()?, rules);
+        let ranking = day04::rank(&bingo, &rules)?;
+        let order: Vec<(usize, usize)> = ranking.wins.iter().map(|w| (w.board, w.ball)).collect();
+        assert_eq!(order, [(2, 24), (0, 16), (1, 13)]);
+        assert_eq!(ranking.wins[0].turn, 12);
+        assert_eq!(ranking.wins[0].score, 4_512);
+        assert_eq!(ranking.wins[2].score, 1_924);
+        assert!(ranking.never_won.is_empty());
+        assert!("rows,squares".parse::<day04::Rules>().is_err());
+
+        // 3 by 3 boards, with balls above 100
+        let scores = |buffer: &str, rules: &str| -> Result<Vec<(usize, u64)>> {
+            let ranking = day04::rank(&buffer.parse()?, &rules.parse()?)?;
+            Ok(ranking.wins.iter().map(|w| (w.board, w.score)).collect())
+        };
+        let buffer = "150,1,5,9,2,3,4,8,7\n\n1 2 3\n4 5 6\n7 8 9\n\n150 1 2\n 9 5 3\n 4 6 8\n";
+        assert_eq!(scores(buffer, "diagonals")?, [(0, 30 * 9), (1, 14 * 4)]);
+        assert_eq!(scores(buffer, "corners")?, [(1, 6 * 8), (0, 6 * 7)]);
+        assert_eq!(scores(buffer, "rows")?[0], (1, 21 * 2));
+        let buffer = "1,2,3\n\n1 2\n3 4\n\n5 6\n7 8\n";
+        let ranking = day04::rank(&buffer.parse()?, &day04::Rules::default())?;
+        assert_eq!(ranking.wins.len(), 1);
+        assert_eq!(ranking.never_won, [1]);
+        let error = day04::run(buffer.to_string()).unwrap_err();
+        assert_eq!(error.message, "board 2 never wins, so no board wins last");
+
+        // Scores past i64::MAX are errors instead of negative answers
+        let error = day04::run("3,1,2\n\n1 2\n4611686018427387904 3\n".to_string()).unwrap_err();
+        assert_eq!(
+            error.message,
+            "first winning score 9223372036854775808 overflows"
+        );
+        assert!("1,2\n\n1 2\n3\n".parse::<day04::Bingo>().is_err());
+        assert!("1,2\n\n1 2\n2 3\n".parse::<day04::Bingo>().is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_05_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day05_sample.txt".to_string())?;
@@ -350,6 +639,102 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_05_floors() -> Result<()> {
+        let buffer = crate::load::data_to_buffer("data/day05_sample.txt".to_string())?;
+        let segments = day05::parse_segments(&buffer)?;
+        let mut floor = day05::Floor::new(&segments);
+        assert!(matches!(floor, day05::Floor::Dense { .. }));
+        assert_eq!(floor.add(&segments), 12);
+
+        // Shift far away, with a point at the origin, so only a sparse floor fits
+        let mut shifted: Vec<day05::Segment> = segments
+            .iter()
+            .map(|segment| day05::Segment {
+                start: (segment.start.0 + 1_000_000, segment.start.1 + 5_000_000),
+                stop: (segment.stop.0 + 1_000_000, segment.stop.1 + 5_000_000),
+            })
+            .collect();
+        shifted.push(day05::Segment {
+            start: (0, 0),
+            stop: (0, 0),
+        });
+        let mut floor = day05::Floor::new(&shifted);
+        assert!(matches!(floor, day05::Floor::Sparse { .. }));
+        assert_eq!(floor.add(&shifted), 12);
+
+        // Many overlaps at one point only count once
+        let segments = vec![day05::parse_segments("3,3 -> 3,3\n")?[0]; 300];
+        assert_eq!(day05::Floor::new(&segments).add(&segments), 1);
+
+        // Long segments cost no more than short ones without a grid
+        let buffer = "0,0 -> 0,33554432\n0,5 -> 0,40000000\n5,0 -> 0,5\n0,3 -> 9,3\n";
+        let segments = day05::parse_segments(buffer)?;
+        let mut floor = day05::Floor::new(&segments);
+        assert!(matches!(floor, day05::Floor::Sparse { .. }));
+        assert_eq!(floor.add(&segments[..2]), 33554428);
+        assert_eq!(floor.add(&segments[2..]), 2);
+
+        let error = day05::parse_segments("0,0 -> 2,2\n0,0 -> 1,2\n").unwrap_err();
+        assert!(error.message.starts_with("line 2:"));
+        assert!(day05::parse_segments("0,0 - 2,2\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_05_heatmap() -> Result<()> {
+        let buffer = crate::load::data_to_buffer("data/day05_sample.txt".to_string())?;
+        let segments = day05::parse_segments(&buffer)?;
+        let heatmap = day05::Heatmap::new(&segments, 100, 100);
+        assert_eq!((heatmap.width, heatmap.height), (10, 10));
+        let points = |segments: &[day05::Segment]| -> u64 {
+            segments
+                .iter()
+                .map(|s| {
+                    std::cmp::max(s.start.0.abs_diff(s.stop.0), s.start.1.abs_diff(s.stop.1)) as u64
+                        + 1
+                })
+                .sum()
+        };
+        let (diagonal, axis): (Vec<_>, Vec<_>) = segments.iter().partition(|s| s.is_diagonal());
+        assert_eq!(heatmap.axis.iter().sum::<u64>(), points(&axis));
+        assert_eq!(heatmap.diagonal.iter().sum::<u64>(), points(&diagonal));
+        // Point (4, 4) has one axis aligned and two diagonal vents
+        assert_eq!(
+            (heatmap.axis[4 + 4 * 10], heatmap.diagonal[4 + 4 * 10]),
+            (1, 2)
+        );
+        assert_eq!(heatmap.colors()[4 + 4 * 10], (170, 42, 85));
+
+        // Binned down, every point still lands in a bin
+        let heatmap = day05::Heatmap::new(&segments, 4, 3);
+        assert_eq!((heatmap.width, heatmap.height), (4, 3));
+        assert_eq!(heatmap.axis.iter().sum::<u64>(), points(&axis));
+        assert_eq!(heatmap.levels().iter().max(), Some(&255));
+
+        // Binned a bin at a time, long segments land in the same bins as
+        // they would point by point
+        let segments = day05::parse_segments("0,0 -> 999,999\n999,0 -> 0,999\n3,1 -> 3,998\n")?;
+        let heatmap = day05::Heatmap::new(&segments, 7, 5);
+        let mut expected = vec![0; 7 * 5];
+        segments.iter().for_each(|s| {
+            let (dx, dy) = (
+                s.stop.0 as i64 - s.start.0 as i64,
+                s.stop.1 as i64 - s.start.1 as i64,
+            );
+            (0..=std::cmp::max(dx.abs(), dy.abs())).for_each(|t| {
+                let x = s.start.0 as i64 + t * dx.signum();
+                let y = s.start.1 as i64 + t * dy.signum();
+                expected[(x * 7 / 1000 + y * 5 / 1000 * 7) as usize] += 1;
+            })
+        });
+        let found: Vec<u64> = (0..7 * 5)
+            .map(|i| heatmap.axis[i] + heatmap.diagonal[i])
+            .collect();
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
     #[test]
     fn test_06_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day06_sample.txt".to_string())?;
@@ -366,11 +751,118 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_06_projection() -> Result<()> {
+        use crate::bignum::BigUint;
+        let buffer = crate::load::data_to_buffer("data/day06_sample.txt".to_string())?;
+        let lifecycle = day06::Lifecycle::default();
+        let fish = day06::parse_population(&buffer, &lifecycle)?;
+        assert_eq!(day06::project::<u128>(&fish, &lifecycle, 18), Some(26));
+        assert_eq!(day06::project::<u128>(&fish, &lifecycle, 80), Some(5_934));
+        assert_eq!(
+            day06::project::<u128>(&fish, &lifecycle, 256),
+            Some(26_984_457_539)
+        );
+        assert_eq!(day06::project::<u128>(&fish, &lifecycle, 3), Some(7));
+
+        // Both backends agree until u128 overflows
+        let mut last = 0;
+        for generations in (0..1_000).step_by(7) {
+            let big: BigUint = day06::project(&fish, &lifecycle, generations).unwrap();
+            match day06::project::<u128>(&fish, &lifecycle, generations) {
+                Some(count) => {
+                    assert_eq!(big, BigUint::from(count));
+                    last = generations;
+                }
+                None => assert!(big.to_string().len() >= 38),
+            }
+        }
+        assert!(last > 900);
+        let count = day06::population(&fish, &lifecycle, 5_000)?;
+        assert!(matches!(count, day06::Population::Big(_)));
+        assert!(count.to_string().len() > 150);
+        assert!(day06::population(&fish, &lifecycle, 1_000_000_000_000_000_000).is_err());
+
+        // Series match the projection, and other lifecycles match a direct simulation
+        let series = day06::series(&fish, &lifecycle, 256)?;
+        assert_eq!(series.len(), 257);
+        assert_eq!(
+            (series[18], series[80], series[256]),
+            (26, 5_934, 26_984_457_539)
+        );
+        let simulate = |lifecycle: &day06::Lifecycle, generations: usize| -> u128 {
+            let mut timers: Vec<usize> = vec![3, 4, 3, 1, 2];
+            (0..generations).for_each(|_| {
+                let spawning = timers.iter().filter(|timer| **timer == 0).count();
+                timers.iter_mut().for_each(|timer| {
+                    *timer = if *timer == 0 {
+                        lifecycle.reset
+                    } else {
+                        *timer - 1
+                    }
+                });
+                timers.extend(std::iter::repeat_n(
+                    lifecycle.newborn,
+                    spawning * lifecycle.offspring,
+                ));
+            });
+            timers.len() as u128
+        };
+        for (reset, newborn, offspring) in [(4, 6, 1), (6, 5, 2), (0, 4, 1), (3, 9, 0)] {
+            let lifecycle = day06::Lifecycle {
+                reset,
+                newborn,
+                offspring,
+            };
+            let fish = day06::parse_population(&buffer, &lifecycle)?;
+            for generations in [0, 5, 12, 30] {
+                let expected = simulate(&lifecycle, generations);
+                assert_eq!(
+                    day06::project::<u128>(&fish, &lifecycle, generations as u64),
+                    Some(expected)
+                );
+                assert_eq!(
+                    day06::series(&fish, &lifecycle, generations)?[generations],
+                    expected
+                );
+            }
+        }
+        let lifecycle = day06::Lifecycle {
+            reset: 2,
+            newborn: 2,
+            offspring: 1,
+        };
+        assert!(day06::parse_population(&buffer, &lifecycle).is_err());
+
+        // A population that never grows never outgrows u128, so long series
+        // are refused up front
+        let lifecycle = day06::Lifecycle {
+            offspring: 0,
+            ..day06::Lifecycle::default()
+        };
+        let fish = day06::parse_population(&buffer, &lifecycle)?;
+        assert_eq!(day06::series(&fish, &lifecycle, 100_000)?.len(), 100_001);
+        assert!(day06::series(&fish, &lifecycle, 1_000_000_000_000).is_err());
+        assert!(day06::series(&fish, &lifecycle, usize::MAX).is_err());
+
+        let big = BigUint::from(u128::MAX);
+        assert_eq!(
+            (&big * &big).to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        assert_eq!(
+            (&big + &BigUint::from(1)).to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_07_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day07_sample.txt".to_string())?;
         let results = day07::run(buffer)?;
         test_day!(results, 37, 168);
+        assert_eq!(results.details, Some((2, 5)));
         Ok(())
     }
 
@@ -382,6 +874,104 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_07_costs() -> Result<()> {
+        let buffer = crate::load::data_to_buffer("data/day07_sample.txt".to_string())?;
+        let positions = day07::parse_positions(&buffer)?;
+        let alignment = |cost: &dyn day07::FuelCost| -> Result<(u32, u64)> {
+            let alignment = day07::align(&positions, cost)?;
+            Ok((alignment.position, alignment.fuel))
+        };
+        assert_eq!(alignment(&day07::Linear)?, (2, 37));
+        assert_eq!(alignment(&day07::Triangular)?, (5, 168));
+
+        // Compare every model against trying every position
+        let brute_force = |cost: &dyn day07::FuelCost| -> (u32, u64) {
+            (0..17)
+                .map(|target| {
+                    let fuel = positions
+                        .iter()
+                        .map(|p| cost.cost(p.abs_diff(target) as u64))
+                        .sum();
+                    (target, fuel)
+                })
+                .min_by_key(|(_, fuel)| *fuel)
+                .unwrap()
+        };
+        let cubic = day07::Custom {
+            cost: |d: u64| d.checked_pow(3),
+            convex: true,
+        };
+        let stepped = day07::Custom {
+            cost: |d: u64| Some(d.div_ceil(3)),
+            convex: false,
+        };
+        let models: [&dyn day07::FuelCost; 5] = [
+            &day07::Linear,
+            &day07::Triangular,
+            &day07::Quadratic,
+            &cubic,
+            &stepped,
+        ];
+        for cost in models.iter() {
+            assert_eq!(alignment(*cost)?, brute_force(*cost));
+        }
+        assert!(day07::align(&[], &day07::Linear).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_07_large() -> Result<()> {
+        // 10^5 crabs with positions up to 10^6, from a linear congruential generator
+        let mut state = 12_345_u64;
+        let positions: Vec<u32> = (0..100_000)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+                ((state >> 33) % 1_000_001) as u32
+            })
+            .collect();
+        let exact = |target: u32, cost: &dyn Fn(u128) -> u128| -> u128 {
+            positions
+                .iter()
+                .map(|p| cost(p.abs_diff(target) as u128))
+                .sum()
+        };
+        type Reference<'a> = &'a dyn Fn(u128) -> u128;
+        let models: [(&dyn day07::FuelCost, Reference); 3] = [
+            (&day07::Linear, &|d| d),
+            (&day07::Triangular, &|d| d * (d + 1) / 2),
+            (&day07::Quadratic, &|d| d * d),
+        ];
+        for (cost, reference) in models.iter() {
+            let alignment = day07::align(&positions, *cost)?;
+            let position = alignment.position;
+            assert_eq!(alignment.fuel as u128, exact(position, reference));
+            assert!(exact(position - 1, reference) > exact(position, reference));
+            assert!(exact(position + 1, reference) >= exact(position, reference));
+        }
+
+        // Totals past u64 are an error, not a wrapped answer
+        let cubic = day07::Custom {
+            cost: |d: u64| d.checked_pow(3),
+            convex: true,
+        };
+        assert!(day07::align(&positions, &cubic).is_err());
+        let quartic = day07::Custom {
+            cost: |d: u64| d.checked_pow(4),
+            convex: true,
+        };
+        assert!(day07::align(&[0, 10_000], &quartic).is_ok());
+        assert!(day07::align(&[0, 1_000_000], &quartic).is_err());
+        let far = [0, u32::MAX];
+        assert_eq!(day07::align(&far, &day07::Triangular)?.fuel, 1 << 62);
+        let crowd: Vec<u32> = (0..10).map(|i| if i < 5 { 0 } else { u32::MAX }).collect();
+        assert!(day07::align(&crowd, &day07::Triangular).is_err());
+        assert!(day07::parse_positions("1,4294967296").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_08_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day08_sample.txt".to_string())?;
@@ -398,6 +988,105 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_08_wiring() -> Result<()> {
+        let entry =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+        let display: day08::Display = entry.parse()?;
+        assert_eq!(display.wiring.to_string(), "abcdefg -> cfgabde");
+        assert_eq!(display.digits, [5, 3, 5, 3]);
+        let standard = day08::GlyphTable::standard();
+        assert_eq!(standard.glyph(&display.wiring, display.patterns[7])?, 7);
+
+        // Malformed, contradictory and undecodable entries are errors
+        let broken = [
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab cdfeb fcadb cdfeb cdbaf",
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb | cdfeb fcadb cdfeb cdbaf",
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ah | cdfeb fcadb cdfeb cdbaf",
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb aab | cdfeb fcadb cdfeb cdbaf",
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ac | cdfeb fcadb cdfeb cdbaf",
+            "acedgfb cdfbe cdfbe fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb abc",
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb",
+        ];
+        for entry in broken.iter() {
+            assert!(entry.parse::<day08::Display>().is_err(), "{}", entry);
+        }
+        assert!(day08::parse_displays("oops", &standard)
+            .unwrap_err()
+            .message
+            .starts_with("line 1:"));
+
+        // Outputs of the standard digits have four digits, so the sum cannot
+        // overflow
+        let long = format!(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab |{}",
+            " acedgfb".repeat(11)
+        );
+        assert_eq!(
+            day08::run(long).unwrap_err().message,
+            "line 1: expected 4 output digits, found 11"
+        );
+        let parsed: day08::GlyphTable = "0 abcefg\n1 cf\n2 acdeg\n3 acdfg\n4 bcdf\n\
+             5 abdfg\n6 abdefg\n7 acf\n8 abcdefg\n9 abcdfg"
+            .parse()?;
+        assert_eq!(parsed, standard);
+
+        // The fast solver for the standard digits agrees with the general one,
+        // which the same digits listed in another order have to use
+        let reversed: day08::GlyphTable = "9 abcdfg\n8 abcdefg\n7 acf\n6 abdefg\n5 abdfg\n\
+             4 bcdf\n3 acdfg\n2 acdeg\n1 cf\n0 abcefg"
+            .parse()?;
+        let buffer = crate::load::data_to_buffer("data/day08_actual.txt".to_string())?;
+        let fast = day08::parse_displays(&buffer, &standard)?;
+        let general = day08::parse_displays(&buffer, &reversed)?;
+        for (fast, general) in fast.iter().zip(general.iter()) {
+            assert_eq!(fast.wiring, general.wiring);
+            let labels: Vec<char> = general.digits.iter().map(|&d| reversed.label(d)).collect();
+            let digits: Vec<char> = fast.digits.iter().map(|&d| standard.label(d)).collect();
+            assert_eq!(labels, digits);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_08_glyphs() -> Result<()> {
+        // Hexadecimal digits on seven segments, through a scrambled wiring
+        let hex: day08::GlyphTable =
+            "0 abcefg\n1 cf\n2 acdeg\n3 acdfg\n4 bcdf\n5 abdfg\n6 abdefg\n\
+             7 acf\n8 abcdefg\n9 abcdfg\nA abcdef\nb bdefg\nC abeg\nd cdefg\nE abdeg\nF abde"
+                .parse()?;
+        let wires = "dgbfcae";
+        let scramble = |glyph: &str| -> String {
+            glyph
+                .bytes()
+                .map(|b| wires.as_bytes()[(b - b'a') as usize] as char)
+                .collect()
+        };
+        let glyphs = [
+            "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg",
+            "abcdfg", "abcdef", "bdefg", "abeg", "cdefg", "abdeg", "abde",
+        ];
+        let key: Vec<String> = glyphs.iter().rev().map(|glyph| scramble(glyph)).collect();
+        let output: Vec<String> = [15, 0, 0, 13]
+            .iter()
+            .map(|&i| scramble(glyphs[i]))
+            .collect();
+        let entry = format!("{} | {}", key.join(" "), output.join(" "));
+        let display = day08::Display::decode(&entry, &hex)?;
+        assert_eq!(display.digits, [15, 0, 0, 13]);
+        assert_eq!(display.wiring.to_string(), "abcdefg -> fceagdb");
+
+        // A table with a symmetry cannot pin down the wiring
+        let symmetric: day08::GlyphTable = "x a\ny b\nz abc".parse()?;
+        let error = day08::Display::decode("a b abc | c", &symmetric).unwrap_err();
+        assert_eq!(error.message, "patterns fit more than one wiring");
+        assert!("1 ab\n2 ab".parse::<day08::GlyphTable>().is_err());
+        let key = key.join(" ");
+        assert!(day08::Display::decode(&format!("{} |", key), &hex).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_09_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day09_sample.txt".to_string())?;
@@ -414,6 +1103,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_09_rectangular() -> Result<()> {
+        let buffer = "21999432\n39878949\n98567898\n".to_string();
+        let results = day09::run(buffer)?;
+        assert_eq!(results.part_1, 20);
+        assert_eq!(results.part_2, 8 * 4 * 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_09_basins() -> Result<()> {
+        let buffer = crate::load::data_to_buffer("data/day09_sample.txt".to_string())?;
+        let smoke = grid::Grid::from_digits(&buffer, 9_u8)?;
+        let (labels, basins) = day09::label_basins(&smoke)?;
+        let mut sizes: Vec<usize> = basins.iter().map(|basin| basin.size()).collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, [3, 9, 9, 14]);
+        assert_eq!(basins[0].low_point, day09::Point { x: 1, y: 0 });
+        assert!(basins[0].cells.contains(&day09::Point { x: 0, y: 1 }));
+        assert_eq!(labels.get(0, 0), 1);
+        assert_eq!(labels.get(2, 0), 0);
+        // Labeling leaves the smoke map intact
+        assert_eq!(smoke.row(0), [2, 1, 9, 9, 9, 4, 3, 2, 1, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_09_large() -> Result<()> {
+        // One basin covering a wide map, far deeper than a recursive fill allows
+        let (width, height) = (1_500, 700);
+        let mut buffer = ("1".repeat(width) + "\n").repeat(height);
+        buffer.replace_range(0..1, "0");
+        let results = day09::run(buffer)?;
+        assert_eq!(results.part_1, 1);
+        assert_eq!(results.part_2, (width * height) as i64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_09_large_basins() -> Result<()> {
+        // Three square basins whose sizes multiply past u32, split by walls
+        let size = 600;
+        let row = vec!["1".repeat(size); 3].join("9") + "\n";
+        let first = vec!["0".to_string() + &"1".repeat(size - 1); 3].join("9") + "\n";
+        let buffer = first + &row.repeat(size - 1);
+        let results = day09::run(buffer)?;
+        assert_eq!(results.part_1, 3);
+        assert_eq!(results.part_2, (size * size).pow(3) as i64);
+        Ok(())
+    }
+
     #[test]
     fn test_10_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day10_sample.txt".to_string())?;
@@ -430,6 +1170,81 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_10_status() -> Result<()> {
+        use day10::Status;
+        let status =
+            |line: &str| -> Result<Status> { Ok(line.parse::<day10::NavigationLine>()?.status) };
+        assert_eq!(status("[<>({}){}[([])<>]]")?, Status::Valid);
+        assert_eq!(
+            status("{([(<{}[<>[]}>{[]{[(<()>")?,
+            Status::Corrupted {
+                column: 13,
+                expected: Some(']'),
+                found: '}'
+            }
+        );
+        assert_eq!(
+            status("()>")?,
+            Status::Corrupted {
+                column: 3,
+                expected: None,
+                found: '>'
+            }
+        );
+        assert_eq!(
+            status("[({(<(())[]>[[{[]{<()<>>")?,
+            Status::Incomplete {
+                completion: "}}]])})]".to_string()
+            }
+        );
+        let error = day10::parse_lines("()\n(a)", &day10::Grammar::default()).unwrap_err();
+        assert_eq!(error.message, "line 2: column 2: unexpected character 'a'");
+        assert!(status("(«)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_10_grammar() -> Result<()> {
+        let grammar: day10::Grammar = "« » 7 1\n( ) 3 2\n⟨ ⟩ 11 3".parse()?;
+        let check = |line: &str| -> Result<day10::Status> {
+            Ok(day10::NavigationLine::check(line, &grammar)?.status)
+        };
+        assert_eq!(check("«(⟨⟩)»")?, day10::Status::Valid);
+        assert_eq!(
+            check("«(⟨»")?,
+            day10::Status::Corrupted {
+                column: 4,
+                expected: Some('⟩'),
+                found: '»'
+            }
+        );
+        assert_eq!(
+            check("«(⟨⟩")?,
+            day10::Status::Incomplete {
+                completion: ")»".to_string()
+            }
+        );
+        assert!(check("<>").is_err());
+
+        // Each bracket belongs to one pair, with two scores
+        assert!("( ) 3".parse::<day10::Grammar>().is_err());
+        assert!("( ) 3 1\n[ ( 57 2".parse::<day10::Grammar>().is_err());
+        assert!("( ( 3 1".parse::<day10::Grammar>().is_err());
+        assert!("() ) 3 1".parse::<day10::Grammar>().is_err());
+        assert!("".parse::<day10::Grammar>().is_err());
+        let error = "( ) 3 1\n[ ] x 2".parse::<day10::Grammar>().unwrap_err();
+        assert!(error.message.starts_with("line 2:"));
+
+        // Scores are checked, and ASCII and other brackets mix
+        let grammar: day10::Grammar = "( ) 18446744073709551615 1\n« » 1 2".parse()?;
+        let chunks = day10::parse_lines(")\n(«", &grammar)?;
+        assert_eq!(day10::scores(&chunks, &grammar)?, (u64::MAX, 11));
+        let chunks = day10::parse_lines(")\n)\n(", &grammar)?;
+        assert!(day10::scores(&chunks, &grammar).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_11_sample() -> Result<()> {
         let buffer = crate::load::data_to_buffer("data/day11_sample.txt".to_string())?;
@@ -445,6 +1260,462 @@ mod tests {
         test_day!(results, 1_637, 242);
         Ok(())
     }
+
+    #[test]
+    fn test_11_generations() -> Result<()> {
+        let buffer = crate::load::data_to_buffer("data/day11_sample.txt".to_string())?;
+        let mut generations = day11::generations(&buffer)?;
+        let first = generations.next().expect("no generation");
+        assert_eq!((first.number, first.flashed.len()), (1, 0));
+        assert_eq!(first.energy.row(0), [6, 5, 9, 4, 2, 5, 4, 3, 3, 4]);
+        let second = generations.next().expect("no generation");
+        assert_eq!(second.flashed.len(), 35);
+        assert_eq!(second.flashed[..2], [(2, 0), (1, 1)]);
+        assert!(second
+            .flashed
+            .iter()
+            .all(|(x, y)| second.energy.get(*x, *y) == 0));
+        let flashes: usize = day11::generations(&buffer)?
+            .take(100)
+            .map(|generation| generation.flashed.len())
+            .sum();
+        assert_eq!(flashes, 1_656);
+        let synchronized = day11::generations(&buffer)?
+            .find(|generation| generation.all_flashed())
+            .expect("never synchronized");
+        assert_eq!(synchronized.number, 195);
+        Ok(())
+    }
+
+    #[test]
+    fn test_11_sizes() -> Result<()> {
+        // A single 9 sets off a cascade across the whole grid in the first step
+        for (width, height) in [(100, 100), (300, 200), (7, 3)] {
+            let mut buffer = ("8".repeat(width) + "\n").repeat(height);
+            buffer.replace_range(0..1, "9");
+            let results = day11::run(buffer)?;
+            assert_eq!(results.part_1, 10 * (width * height) as i64);
+            assert_eq!(results.part_2, 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_11_never_synchronized() -> Result<()> {
+        for buffer in ["05\n", "0\n5\n", "00000\n55555\n", "1234\n"] {
+            assert!(day11::run(buffer.to_string()).is_err());
+            let repeated = day11::generations(buffer)?
+                .take(1_000)
+                .find(|generation| generation.repeated)
+                .expect("energies never repeated");
+            assert!(!repeated.all_flashed());
+        }
+
+        // Synchronizing before part 1 ends is found without searching further
+        let results = day11::run("9\n".to_string())?;
+        assert_eq!((results.part_1, results.part_2), (10, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_grid() -> Result<()> {
+        let buffer = crate::load::data_to_buffer("data/day09_sample.txt".to_string())?;
+        let smoke = grid::Grid::from_digits(&buffer, 9_u8)?;
+        assert_eq!((smoke.width(), smoke.height(), smoke.halo()), (10, 5, 9));
+        assert_eq!(smoke.row(1), [3, 9, 8, 7, 8, 9, 4, 9, 2, 1]);
+        assert_eq!(smoke.rows().count(), 5);
+        assert_eq!(
+            smoke.column(9).copied().collect::<Vec<_>>(),
+            [0, 1, 2, 9, 8]
+        );
+        let corner = smoke.index(0, 0);
+        assert_eq!(
+            smoke
+                .neighbours_4(corner)
+                .map(|n| smoke[n])
+                .collect::<Vec<_>>(),
+            [1, 3, 9, 9]
+        );
+        assert_eq!(
+            smoke.neighbours_8(corner).map(|n| smoke[n]).sum::<u8>(),
+            1 + 9 + 3 + 5 * 9
+        );
+        assert_eq!(smoke.coordinates(smoke.index(7, 3)), (7, 3));
+        assert!(grid::Grid::from_digits("123\n45\n", 0_u8).is_err());
+        assert!(grid::Grid::from_digits("12a\n", 0_u8).is_err());
+        Ok(())
+    }
+
+    // Minimal HTTP server that answers each request with a canned body and
+    // records the request line, cookie, and form body it saw
+    fn stub_server(responses: Vec<&'static str>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let base_url = format!("http://{}", listener.local_addr().expect("no address"));
+        let handle = std::thread::spawn(move || {
+            responses
+                .iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().expect("failed to accept");
+                    let mut reader =
+                        std::io::BufReader::new(stream.try_clone().expect("failed to clone"));
+                    let mut request = String::new();
+                    let mut line = String::new();
+                    let mut content_length = 0;
+                    while reader.read_line(&mut line).expect("failed to read") > 2 {
+                        let lower = line.to_lowercase();
+                        if request.is_empty() {
+                            request = line.trim().to_string();
+                        } else if lower.starts_with("cookie:") {
+                            request += &format!(" {}", line[7..].trim());
+                        } else if lower.starts_with("content-length:") {
+                            content_length = line[15..].trim().parse().expect("bad length");
+                        }
+                        line.clear();
+                    }
+                    let mut body = vec![0; content_length];
+                    reader.read_exact(&mut body).expect("failed to read body");
+                    request += &format!(" {}", String::from_utf8_lossy(&body));
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        response.len(),
+                        response
+                    )
+                    .expect("failed to write");
+                    request.trim().to_string()
+                })
+                .collect()
+        });
+        (base_url, handle)
+    }
+
+    #[test]
+    fn test_cli_parse() -> Result<()> {
+        let args = |line: &str| {
+            line.split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(cli::parse(args("").into_iter())?, cli::Command::Run);
+        assert_eq!(
+            cli::parse(args("fetch 3 --force 5").into_iter())?,
+            cli::Command::Fetch {
+                days: vec![3, 5],
+                force: true
+            }
+        );
+        assert_eq!(
+            cli::parse(args("submit 2 1 150").into_iter())?,
+            cli::Command::Submit {
+                day: 2,
+                part: 1,
+                answer: "150".to_string()
+            }
+        );
+        match cli::parse(args("day 9 input.txt --render --ppm=basins.ppm").into_iter())? {
+            cli::Command::Day {
+                day,
+                input,
+                options,
+            } => {
+                assert_eq!((day, input.as_deref()), (9, Some("input.txt")));
+                assert!(options.flag("render"));
+                assert_eq!(options.value("ppm"), Some("basins.ppm"));
+                assert!(options.check(&["render", "ppm"]).is_ok());
+                assert!(options.check(&["render"]).is_err());
+            }
+            command => panic!("unexpected command {:?}", command),
+        }
+        assert!(cli::parse(args("submit 2 3 150").into_iter()).is_err());
+        assert!(cli::parse(args("fetch 26").into_iter()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_remote_fetch() -> Result<()> {
+        let (base_url, server) = stub_server(vec!["199\n200\n208\n"]);
+        let data_dir =
+            std::env::temp_dir().join(format!("advent2021_fetch_{}", std::process::id()));
+        let mut client = remote::Client::new(remote::Config {
+            base_url,
+            session: Some("token".to_string()),
+            data_dir: data_dir.to_string_lossy().to_string(),
+            rate_limit: std::time::Duration::from_millis(0),
+            ..remote::Config::default()
+        });
+        assert!(client.fetch_input(1, false)?);
+        assert!(!client.fetch_input(1, false)?);
+        let buffer = crate::load::data_to_buffer(client.input_path(1))?;
+        assert_eq!(buffer, "199\n200\n208\n");
+        let requests = server.join().expect("server failed");
+        assert_eq!(requests, ["GET /2021/day/1/input HTTP/1.1 session=token"]);
+        std::fs::remove_dir_all(data_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_remote_submit() -> Result<()> {
+        let (base_url, server) = stub_server(vec![
+            "<p>That's the right answer! You are one gold star closer.</p>",
+            "<p>That's not the right answer; your answer is too low.</p>",
+            "<p>You gave an answer too recently. You have 42s left to wait.</p>",
+            "<p>You don't seem to be solving the right level.</p>",
+        ]);
+        let data_dir =
+            std::env::temp_dir().join(format!("advent2021_submit_{}", std::process::id()));
+        // A new client for each submission, like separate runs of the command
+        let client = || {
+            remote::Client::new(remote::Config {
+                base_url: base_url.clone(),
+                session: Some("token".to_string()),
+                data_dir: data_dir.to_string_lossy().to_string(),
+                rate_limit: std::time::Duration::from_millis(20),
+                ..remote::Config::default()
+            })
+        };
+        let start = Instant::now();
+        assert_eq!(client().submit(2, 1, "150")?, remote::Submission::Correct);
+        assert_eq!(
+            client().submit(2, 2, "899")?,
+            remote::Submission::Incorrect {
+                hint: Some("too low".to_string())
+            }
+        );
+        assert_eq!(
+            client().submit(2, 2, "901")?,
+            remote::Submission::TooSoon {
+                wait: Some("42s".to_string())
+            }
+        );
+        assert_eq!(
+            client().submit(2, 1, "150")?,
+            remote::Submission::AlreadySolved
+        );
+        assert!(start.elapsed() >= std::time::Duration::from_millis(60));
+        let requests = server.join().expect("server failed");
+        assert_eq!(
+            requests[1],
+            "POST /2021/day/2/answer HTTP/1.1 session=token level=2&answer=899"
+        );
+        std::fs::remove_dir_all(data_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_property_01() {
+        testing::check(
+            day01::run,
+            testing::generate_depths,
+            testing::reference_depths,
+            2000,
+        );
+    }
+
+    #[test]
+    fn test_property_02() {
+        testing::check(
+            day02::run,
+            testing::generate_commands,
+            testing::reference_commands,
+            2000,
+        );
+    }
+
+    #[test]
+    fn test_property_03() {
+        testing::check(
+            day03::run,
+            testing::generate_report,
+            testing::reference_report,
+            2000,
+        );
+    }
+
+    #[test]
+    fn test_property_04() {
+        testing::check(
+            day04::run,
+            testing::generate_bingo,
+            testing::reference_bingo,
+            2000,
+        );
+    }
+
+    #[test]
+    fn test_property_05() {
+        testing::check(
+            day05::run,
+            testing::generate_segments,
+            testing::reference_segments,
+            2000,
+        );
+    }
+
+    #[test]
+    fn test_property_05_sparse() -> Result<()> {
+        // A far away point leaves the overlaps alone but rules out a grid
+        for seed in 0..2000 {
+            let input = testing::generate_segments(&mut testing::Rng::new(seed));
+            let mut segments = day05::parse_segments(&input)?;
+            segments.push(day05::parse_segments("4000000000,9 -> 4000000000,9")?[0]);
+            let (diagonal, axis): (Vec<_>, Vec<_>) =
+                segments.iter().partition(|segment| segment.is_diagonal());
+            let mut floor = day05::Floor::new(&segments);
+            assert!(matches!(floor, day05::Floor::Sparse { .. }));
+            let count_1 = floor.add(&axis) as i64;
+            let count_2 = count_1 + floor.add(&diagonal) as i64;
+            assert_eq!(
+                Some((count_1, count_2)),
+                testing::reference_segments(&input),
+                "seed {} with input:\n{}",
+                seed,
+                input
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_property_06() {
+        testing::check(
+            day06::run,
+            testing::generate_fish,
+            testing::reference_fish,
+            2000,
+        );
+    }
+
+    #[test]
+    fn test_property_07() {
+        testing::check(
+            day07::run,
+            testing::generate_crabs,
+            testing::reference_crabs,
+            1000,
+        );
+    }
+
+    #[test]
+    fn test_property_08() {
+        testing::check(
+            day08::run,
+            testing::generate_displays,
+            testing::reference_displays,
+            500,
+        );
+    }
+
+    #[test]
+    fn test_property_09() {
+        testing::check(
+            day09::run,
+            testing::generate_heightmap,
+            testing::reference_heightmap,
+            2000,
+        );
+    }
+
+    #[test]
+    fn test_property_10() {
+        testing::check(
+            day10::run,
+            testing::generate_brackets,
+            testing::reference_brackets,
+            2000,
+        );
+    }
+
+    #[test]
+    fn test_property_06_series() -> Result<()> {
+        let lifecycle = day06::Lifecycle::default();
+        for seed in 0..1000 {
+            let input = testing::generate_fish(&mut testing::Rng::new(seed));
+            let population = day06::parse_population(&input, &lifecycle)?;
+            let expected = testing::simulate_each_fish(&input, 30);
+            assert_eq!(
+                day06::series(&population, &lifecycle, 30)?,
+                expected,
+                "seed {}",
+                seed
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_property_11() -> Result<()> {
+        for seed in 0..300 {
+            let input = testing::generate_octopuses(&mut testing::Rng::new(seed));
+            let expected = testing::simulate_octopuses(&input, 150);
+            let found: Vec<usize> = day11::generations(&input)?
+                .take(150)
+                .map(|generation| generation.flashed.len())
+                .collect();
+            assert_eq!(found, expected, "seed {} with input:\n{}", seed, input);
+
+            // The solver finds the first synchronized flash, or gives up once
+            // the energies repeat
+            let results = day11::run(input.clone())
+                .map(|results| (results.part_1, results.part_2))
+                .ok();
+            match testing::reference_octopuses(&input, 1000) {
+                Some(answers) => assert_eq!(
+                    results,
+                    Some(answers),
+                    "seed {} with input:\n{}",
+                    seed,
+                    input
+                ),
+                None => assert!(
+                    results
+                        .filter(|(_, generation)| *generation <= 1000)
+                        .is_none(),
+                    "seed {} with input:\n{}",
+                    seed,
+                    input
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fuzz() -> Result<()> {
+        for (seed, (name, target)) in fuzz::TARGETS.iter().enumerate() {
+            let corpus = fuzz::load_corpus(name)?;
+            let crashes = fuzz::fuzz(*target, &corpus, fuzz::iterations(), seed as u64);
+            assert!(crashes.is_empty(), "{} panicked on {:?}", name, crashes[0]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fuzz_regressions() -> Result<()> {
+        // Inputs the fuzzer once crashed on are now rejected
+        assert!(day01::run("199\n2OO\n".to_string()).is_err());
+        let buffer = "forward 5\ndown 2147483647\nforward 8\nup 3\ndown 8\nforward 2\n";
+        assert!(day02::run(buffer.to_string()).is_err());
+        let buffer = "repeat 100000\nrepeat 100000\nend\nend\n";
+        assert!(day02::run(buffer.to_string()).is_err());
+        assert!(day04::run("3,1,2\n\n1 2\n18446744073709551615 1\n".to_string()).is_err());
+        let results = day04::run("3,1,2\n\n1 2\n4294967296 3\n".to_string())?;
+        assert_eq!(results.part_1, 4294967296 * 2);
+        assert!(day04::run("3,1,2\n\n1 2\n18446744073709551615 3\n".to_string()).is_err());
+        let results = day05::run("0,9 -> 0,4294967295\n0,5 -> 0,4294967295\n".to_string())?;
+        assert_eq!(results.part_1, 4294967287);
+        assert!(day05::parse_segments("0,9 -> 0,18446744073709551615\n").is_err());
+        let buffer = crate::load::data_to_buffer("fuzz/corpus/day08/long_output.txt".to_string())?;
+        assert!(day08::run(buffer).is_err());
+
+        // Crabs far apart gallop without overflowing the step
+        let alignment = day07::align(&[0, 4294967295], &day07::Linear);
+        assert_eq!(
+            alignment.map(|alignment| alignment.fuel).ok(),
+            Some(4294967295)
+        );
+        Ok(())
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -43,6 +43,12 @@ pub(crate) fn print_part(part: usize, output: &str, output_value: &str) -> crate
     Ok(())
 }
 
+// Another value for the part printed last
+pub(crate) fn print_detail(output: &str, output_value: &str) -> crate::Result<()> {
+    println!("      {}: {}", output, output_value);
+    Ok(())
+}
+
 // -----------------------------------------------------------------------------
 // Output timing summary
 // -----------------------------------------------------------------------------
@@ -106,8 +112,8 @@ pub(crate) fn print_timing(times: &Timing) -> crate::Result<()> {
 // Output timing comparison
 // -----------------------------------------------------------------------------
 pub(crate) fn print_days_timing(
-    times: &Vec<std::time::Duration>,
-    std_devs: &Vec<f64>,
+    times: &[std::time::Duration],
+    std_devs: &[f64],
 ) -> crate::Result<()> {
     println!("- {}", "Timing Comparison".bold());
     println!("    {}: {}", "Repetitions".purple().bold(), REPETITIONS);
@@ -155,3 +161,232 @@ pub(crate) fn print_days_timing(
 }
 
 // -----------------------------------------------------------------------------
+// Distinct colors
+// -----------------------------------------------------------------------------
+pub(crate) fn palette(index: usize) -> (u8, u8, u8) {
+    // Step around the hue circle by the golden ratio so neighbours differ
+    let hue = (index as f64 * 0.618_033_988_749_895).fract() * 6.0;
+    let (saturation, value) = (0.65, 0.95);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as usize {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let to_byte = |channel: f64| ((channel + m) * 255.0).round() as u8;
+    (to_byte(r), to_byte(g), to_byte(b))
+}
+
+// -----------------------------------------------------------------------------
+// Write binary PPM image, scaling each cell to a square of pixels
+// -----------------------------------------------------------------------------
+pub(crate) fn write_ppm(
+    path: &str,
+    width: usize,
+    height: usize,
+    scale: usize,
+    pixels: &[(u8, u8, u8)],
+) -> crate::Result<()> {
+    let mut image = format!("P6\n{} {}\n255\n", width * scale, height * scale).into_bytes();
+    image.reserve(3 * width * height * scale * scale);
+    pixels.chunks(width).take(height).for_each(|row| {
+        (0..scale).for_each(|_| {
+            row.iter()
+                .for_each(|&(r, g, b)| (0..scale).for_each(|_| image.extend_from_slice(&[r, g, b])))
+        })
+    });
+    std::fs::write(path, image)?;
+    println!("    {}: {}", "Image".purple().bold(), path);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Write binary PGM image, scaling each cell to a square of pixels
+// -----------------------------------------------------------------------------
+pub(crate) fn write_pgm(
+    path: &str,
+    width: usize,
+    height: usize,
+    scale: usize,
+    pixels: &[u8],
+) -> crate::Result<()> {
+    let mut image = format!("P5\n{} {}\n255\n", width * scale, height * scale).into_bytes();
+    image.reserve(width * height * scale * scale);
+    pixels.chunks(width).take(height).for_each(|row| {
+        (0..scale).for_each(|_| {
+            row.iter()
+                .for_each(|&level| (0..scale).for_each(|_| image.push(level)))
+        })
+    });
+    std::fs::write(path, image)?;
+    println!("    {}: {}", "Image".purple().bold(), path);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Write CSV file
+// -----------------------------------------------------------------------------
+pub(crate) fn write_csv(path: &str, header: &[&str], rows: &[Vec<String>]) -> crate::Result<()> {
+    let mut csv = header.join(",") + "\n";
+    rows.iter().for_each(|row| {
+        csv += &row.join(",");
+        csv += "\n";
+    });
+    std::fs::write(path, csv)?;
+    println!("    {}: {}", "CSV".purple().bold(), path);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Sparkline of values, averaged down to at most `width` characters
+// -----------------------------------------------------------------------------
+pub(crate) fn sparkline(values: &[f64], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let bucket_size = values.len().div_ceil(width);
+    let buckets: Vec<f64> = values
+        .chunks(bucket_size)
+        .map(|bucket| bucket.iter().sum::<f64>() / bucket.len() as f64)
+        .collect();
+    let min = buckets.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = buckets.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max > min { max - min } else { 1.0 };
+    buckets
+        .iter()
+        .map(|value| BARS[(((value - min) / range) * (BARS.len() - 1) as f64).round() as usize])
+        .collect()
+}
+
+// -----------------------------------------------------------------------------
+// ASCII scatter plot of (x, y) points, with y increasing downwards
+// -----------------------------------------------------------------------------
+pub(crate) fn ascii_plot(points: &[(f64, f64)], width: usize, height: usize) -> Vec<String> {
+    if points.is_empty() || width == 0 || height == 0 {
+        return vec![];
+    }
+    let range = |values: &mut dyn Iterator<Item = f64>| {
+        values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        })
+    };
+    let (x_min, x_max) = range(&mut points.iter().map(|point| point.0));
+    let (y_min, y_max) = range(&mut points.iter().map(|point| point.1));
+    let scale = |value: f64, min: f64, max: f64, cells: usize| {
+        if max > min {
+            ((value - min) / (max - min) * (cells - 1) as f64).round() as usize
+        } else {
+            0
+        }
+    };
+    let mut canvas = vec![vec![' '; width]; height];
+    points.iter().for_each(|(x, y)| {
+        canvas[scale(*y, y_min, y_max, height)][scale(*x, x_min, x_max, width)] = '•';
+    });
+    let label_width = std::cmp::max(y_min.to_string().len(), y_max.to_string().len());
+    let mut lines: Vec<String> = canvas
+        .into_iter()
+        .enumerate()
+        .map(|(row, cells)| {
+            let label = match row {
+                0 => y_min.to_string(),
+                _ if row == height - 1 => y_max.to_string(),
+                _ => String::new(),
+            };
+            format!(
+                "{:>w$} │{}",
+                label,
+                cells.into_iter().collect::<String>(),
+                w = label_width
+            )
+        })
+        .collect();
+    lines.push(format!(
+        "{:>w$} └{}",
+        "",
+        "─".repeat(width),
+        w = label_width
+    ));
+    lines.push(format!(
+        "{:>w$}  {:<half$}{:>half$}",
+        "",
+        x_min,
+        x_max,
+        w = label_width,
+        half = width / 2
+    ));
+    lines
+}
+
+// -----------------------------------------------------------------------------
+// Write SVG line plot, with y increasing downwards
+// -----------------------------------------------------------------------------
+pub(crate) fn write_svg_plot(
+    path: &str,
+    title: &str,
+    series: &[(&str, &[f64])],
+) -> crate::Result<()> {
+    const WIDTH: f64 = 800.0;
+    const HEIGHT: f64 = 400.0;
+    const MARGIN: f64 = 40.0;
+    let values = || series.iter().flat_map(|(_, values)| values.iter().cloned());
+    let length = series
+        .iter()
+        .map(|(_, values)| values.len())
+        .max()
+        .unwrap_or(0);
+    let min = values().fold(f64::INFINITY, f64::min);
+    let max = values().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max > min { max - min } else { 1.0 };
+    let x_scale = (WIDTH - 2.0 * MARGIN) / std::cmp::max(length - 1, 1) as f64;
+    let y_scale = (HEIGHT - 2.0 * MARGIN) / range;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"16\">{}</text>\n\
+         <text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"12\">{}</text>\n\
+         <text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"12\">{}</text>\n",
+        WIDTH,
+        HEIGHT,
+        MARGIN,
+        MARGIN / 2.0,
+        title,
+        4.0,
+        MARGIN + 4.0,
+        min,
+        4.0,
+        HEIGHT - MARGIN,
+        max,
+    );
+    series.iter().for_each(|(color, values)| {
+        let points: Vec<String> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                format!(
+                    "{:.1},{:.1}",
+                    MARGIN + i as f64 * x_scale,
+                    MARGIN + (value - min) * y_scale
+                )
+            })
+            .collect();
+        svg += &format!(
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1\" points=\"{}\"/>\n",
+            color,
+            points.join(" ")
+        );
+    });
+    svg += "</svg>\n";
+    std::fs::write(path, svg)?;
+    println!("    {}: {}", "Plot".purple().bold(), path);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
@@ -0,0 +1,250 @@
+//! Remote:
+//! This module has the code for downloading puzzle input from and submitting
+//! answers to the Advent of Code server. The session token and base URL come
+//! from a small config file or from environment variables, so the same code
+//! can be pointed at a local stub server. Requests are spaced out by a minimum
+//! interval to stay polite to the real server. Every command runs in its own
+//! process, so the time of the last request is kept in a file in the data
+//! directory.
+
+use crate::prelude::*;
+
+// Constants
+const YEAR: usize = 2021;
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+const DEFAULT_CONFIG_FILE: &str = ".aoc";
+const DEFAULT_DATA_DIR: &str = "data";
+const DEFAULT_RATE_LIMIT_MS: u64 = 1_000;
+const DEFAULT_USER_AGENT: &str = "github.com/jeremylt/advent2021";
+const LAST_REQUEST_FILE: &str = ".last_request";
+
+// -----------------------------------------------------------------------------
+// Config data struct
+// -----------------------------------------------------------------------------
+#[derive(Debug)]
+pub(crate) struct Config {
+    pub(crate) base_url: String,
+    pub(crate) session: Option<String>,
+    pub(crate) data_dir: String,
+    pub(crate) rate_limit: std::time::Duration,
+    pub(crate) user_agent: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            session: None,
+            data_dir: DEFAULT_DATA_DIR.to_string(),
+            rate_limit: std::time::Duration::from_millis(DEFAULT_RATE_LIMIT_MS),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+}
+
+impl Config {
+    // Read `key = value` lines from the config file, then let environment
+    // variables override them
+    pub(crate) fn load() -> crate::Result<Self> {
+        let mut config = Self::default();
+        let config_file =
+            std::env::var("AOC_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_FILE.to_string());
+        if let Ok(contents) = std::fs::read_to_string(&config_file) {
+            for line in contents.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let (key, value) = line.split_once('=').ok_or(crate::Error {
+                    message: format!("invalid line in {}: {}", config_file, line),
+                })?;
+                config.set(key.trim(), value.trim())?;
+            }
+        }
+        for (variable, key) in [
+            ("AOC_BASE_URL", "base_url"),
+            ("AOC_SESSION", "session"),
+            ("AOC_DATA_DIR", "data_dir"),
+            ("AOC_RATE_LIMIT_MS", "rate_limit_ms"),
+            ("AOC_USER_AGENT", "user_agent"),
+        ] {
+            if let Ok(value) = std::env::var(variable) {
+                config.set(key, value.trim())?;
+            }
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> crate::Result<()> {
+        match key {
+            "base_url" => self.base_url = value.trim_end_matches('/').to_string(),
+            "session" => self.session = Some(value.to_string()),
+            "data_dir" => self.data_dir = value.to_string(),
+            "rate_limit_ms" => {
+                self.rate_limit = std::time::Duration::from_millis(value.parse()?);
+            }
+            "user_agent" => self.user_agent = value.to_string(),
+            _ => {
+                return Err(crate::Error {
+                    message: format!("unknown config key '{}'", key),
+                })
+            }
+        }
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Submission result enum
+// -----------------------------------------------------------------------------
+#[derive(Debug, PartialEq)]
+pub(crate) enum Submission {
+    Correct,
+    Incorrect { hint: Option<String> },
+    TooSoon { wait: Option<String> },
+    AlreadySolved,
+    Unrecognized,
+}
+
+impl Submission {
+    // The server answers with a full HTML page, so look for the key phrases
+    fn from_response(body: &str) -> Self {
+        if body.contains("That's the right answer") {
+            Self::Correct
+        } else if body.contains("That's not the right answer") {
+            let hint = ["too high", "too low"]
+                .iter()
+                .find(|hint| body.contains(*hint))
+                .map(|hint| hint.to_string());
+            Self::Incorrect { hint }
+        } else if body.contains("You gave an answer too recently") {
+            let wait = body.find("You have ").and_then(|start| {
+                body[start..]
+                    .find(" left to wait")
+                    .map(|stop| body[start + 9..start + stop].to_string())
+            });
+            Self::TooSoon { wait }
+        } else if body.contains("You don't seem to be solving the right level") {
+            Self::AlreadySolved
+        } else {
+            Self::Unrecognized
+        }
+    }
+}
+
+impl fmt::Display for Submission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Correct => write!(f, "{}", "correct".green().bold()),
+            Self::Incorrect { hint: Some(hint) } => {
+                write!(f, "{} ({})", "incorrect".red().bold(), hint)
+            }
+            Self::Incorrect { hint: None } => write!(f, "{}", "incorrect".red().bold()),
+            Self::TooSoon { wait: Some(wait) } => {
+                write!(f, "{} ({} left to wait)", "too soon".yellow().bold(), wait)
+            }
+            Self::TooSoon { wait: None } => write!(f, "{}", "too soon".yellow().bold()),
+            Self::AlreadySolved => write!(f, "{}", "already solved".blue().bold()),
+            Self::Unrecognized => write!(f, "{}", "unrecognized response".purple().bold()),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Client data struct
+// -----------------------------------------------------------------------------
+pub(crate) struct Client {
+    config: Config,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub(crate) fn new(config: Config) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(&config.user_agent)
+            .timeout(std::time::Duration::from_secs(30))
+            .build();
+        Self { config, agent }
+    }
+
+    pub(crate) fn input_path(&self, day: usize) -> String {
+        format!("{}/day{:02}_actual.txt", self.config.data_dir, day)
+    }
+
+    fn session(&self) -> crate::Result<String> {
+        let session = self.config.session.as_ref().ok_or(crate::Error {
+            message: "session token not found, set AOC_SESSION or add `session = ...` to .aoc"
+                .to_string(),
+        })?;
+        Ok(format!("session={}", session))
+    }
+
+    // Wait out the rate limit since the last request by any run, then record
+    // this one as milliseconds since the epoch
+    fn throttle(&mut self) -> crate::Result<()> {
+        let path = format!("{}/{}", self.config.data_dir, LAST_REQUEST_FILE);
+        let now = || {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+        };
+        let last_request = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| contents.trim().parse().ok())
+            .map(std::time::Duration::from_millis);
+        if let Some(last_request) = last_request {
+            let elapsed = now().saturating_sub(last_request);
+            if elapsed < self.config.rate_limit {
+                std::thread::sleep(self.config.rate_limit - elapsed);
+            }
+        }
+        std::fs::create_dir_all(&self.config.data_dir)?;
+        std::fs::write(&path, now().as_millis().to_string())?;
+        Ok(())
+    }
+
+    // Download the input for a day unless it is already cached, returning
+    // whether a request was made
+    pub(crate) fn fetch_input(&mut self, day: usize, force: bool) -> crate::Result<bool> {
+        let path = self.input_path(day);
+        if !force && std::path::Path::new(&path).exists() {
+            return Ok(false);
+        }
+        let cookie = self.session()?;
+        self.throttle()?;
+        let input = self
+            .agent
+            .get(&format!(
+                "{}/{}/day/{}/input",
+                self.config.base_url, YEAR, day
+            ))
+            .set("Cookie", &cookie)
+            .call()?
+            .into_string()?;
+        std::fs::create_dir_all(&self.config.data_dir)?;
+        std::fs::write(&path, input)?;
+        Ok(true)
+    }
+
+    pub(crate) fn submit(
+        &mut self,
+        day: usize,
+        part: usize,
+        answer: &str,
+    ) -> crate::Result<Submission> {
+        let cookie = self.session()?;
+        self.throttle()?;
+        let body = self
+            .agent
+            .post(&format!(
+                "{}/{}/day/{}/answer",
+                self.config.base_url, YEAR, day
+            ))
+            .set("Cookie", &cookie)
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])?
+            .into_string()?;
+        Ok(Submission::from_response(&body))
+    }
+}
+
+// -----------------------------------------------------------------------------
@@ -0,0 +1,679 @@
+//! Testing:
+//! This module has random input generators and brute force references for
+//! every day, so the fast solvers can be checked on many more inputs than
+//! the samples. The references take the slowest obvious route through each
+//! puzzle and share no code with the days. Inputs come from a small seeded
+//! generator, so a failing case is reproduced from its seed alone.
+
+use crate::RunData;
+use std::collections::{HashMap, VecDeque};
+
+// -----------------------------------------------------------------------------
+// Seedable random number generator
+// -----------------------------------------------------------------------------
+// SplitMix64, which is plenty for test inputs
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in `low..=high`, the slight modulo bias does not matter here
+    pub(crate) fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next_u64() % (high - low + 1)
+    }
+
+    // True one time in `n`
+    pub(crate) fn one_in(&mut self, n: u64) -> bool {
+        self.next_u64().is_multiple_of(n)
+    }
+
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0, i as u64) as usize);
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Differential check of a day against its reference
+// -----------------------------------------------------------------------------
+// Both answers from the reference, or None when the day should fail
+pub(crate) type Answers = Option<(i64, i64)>;
+
+pub(crate) fn check(
+    run: fn(String) -> crate::Result<RunData>,
+    generate: fn(&mut Rng) -> String,
+    reference: fn(&str) -> Answers,
+    cases: u64,
+) {
+    for seed in 0..cases {
+        let input = generate(&mut Rng::new(seed));
+        let expected = reference(&input);
+        let found = run(input.clone())
+            .ok()
+            .map(|results| (results.part_1, results.part_2));
+        assert_eq!(found, expected, "seed {} with input:\n{}", seed, input);
+    }
+}
+
+fn lines<T: ToString>(items: impl Iterator<Item = T>) -> String {
+    items.map(|item| item.to_string() + "\n").collect()
+}
+
+// -----------------------------------------------------------------------------
+// Day 1
+// -----------------------------------------------------------------------------
+pub(crate) fn generate_depths(rng: &mut Rng) -> String {
+    let count = rng.range(1, 60);
+    lines((0..count).map(|_| rng.range(0, 200)))
+}
+
+pub(crate) fn reference_depths(buffer: &str) -> Answers {
+    let depths: Vec<i64> = buffer.lines().map(|line| line.parse().unwrap()).collect();
+    let increases = |window: usize| {
+        let sums: Vec<i64> = depths.windows(window).map(|w| w.iter().sum()).collect();
+        sums.windows(2).filter(|pair| pair[1] > pair[0]).count() as i64
+    };
+    Some((increases(1), increases(3)))
+}
+
+// -----------------------------------------------------------------------------
+// Day 2
+// -----------------------------------------------------------------------------
+pub(crate) fn generate_commands(rng: &mut Rng) -> String {
+    let count = rng.range(0, 40);
+    lines((0..count).map(|_| {
+        let name = ["forward", "down", "up"][rng.range(0, 2) as usize];
+        format!("{} {}", name, rng.range(0, 9))
+    }))
+}
+
+pub(crate) fn reference_commands(buffer: &str) -> Answers {
+    let (mut horizontal, mut depth_1, mut depth_2, mut aim) = (0, 0, 0, 0);
+    for line in buffer.lines() {
+        let (name, value) = line.split_once(' ')?;
+        let value: i64 = value.parse().ok()?;
+        match name {
+            "forward" => {
+                horizontal += value;
+                depth_2 += aim * value;
+            }
+            "down" => {
+                depth_1 += value;
+                aim += value;
+            }
+            "up" => {
+                depth_1 -= value;
+                aim -= value;
+            }
+            _ => return None,
+        }
+    }
+    Some((horizontal * depth_1, horizontal * depth_2))
+}
+
+// -----------------------------------------------------------------------------
+// Day 3
+// -----------------------------------------------------------------------------
+pub(crate) fn generate_report(rng: &mut Rng) -> String {
+    let width = rng.range(1, 12);
+    let count = rng.range(1, 30);
+    lines((0..count).map(|_| {
+        (0..width)
+            .map(|_| if rng.one_in(2) { '1' } else { '0' })
+            .collect::<String>()
+    }))
+}
+
+pub(crate) fn reference_report(buffer: &str) -> Answers {
+    let rows: Vec<Vec<u8>> = buffer.lines().map(|line| line.bytes().collect()).collect();
+    let width = rows[0].len();
+    let ones =
+        |rows: &[&Vec<u8>], column: usize| rows.iter().filter(|row| row[column] == b'1').count();
+    let value = |bits: &[bool]| bits.iter().fold(0, |value, &bit| value * 2 + bit as i64);
+
+    // Gamma takes bits set in more than half of the rows
+    let all: Vec<&Vec<u8>> = rows.iter().collect();
+    let gamma: Vec<bool> = (0..width).map(|j| 2 * ones(&all, j) > rows.len()).collect();
+    let epsilon: Vec<bool> = gamma.iter().map(|bit| !bit).collect();
+
+    // Keep rows matching the most or least common bit until one is left
+    let rating = |most_common: bool| {
+        let mut kept = all.clone();
+        for j in 0..width {
+            if kept.len() == 1 {
+                break;
+            }
+            let (ones, zeros) = (ones(&kept, j), kept.len() - ones(&kept, j));
+            let bit = match (most_common, ones, zeros) {
+                (true, ones, zeros) => ones >= zeros,
+                (false, 0, _) => false,
+                (false, _, 0) => true,
+                (false, ones, zeros) => ones < zeros,
+            };
+            let wanted = if bit { b'1' } else { b'0' };
+            kept.retain(|row| row[j] == wanted);
+        }
+        let bits: Vec<bool> = kept[0].iter().map(|&b| b == b'1').collect();
+        value(&bits)
+    };
+    Some((
+        value(&gamma) * value(&epsilon),
+        rating(true) * rating(false),
+    ))
+}
+
+// -----------------------------------------------------------------------------
+// Day 4
+// -----------------------------------------------------------------------------
+pub(crate) fn generate_bingo(rng: &mut Rng) -> String {
+    let size = rng.range(1, 5) as usize;
+    let numbers = rng.range((size * size) as u64, 60);
+    let mut balls: Vec<u64> = (0..numbers).collect();
+    rng.shuffle(&mut balls);
+    balls.truncate(rng.range(1, numbers) as usize);
+    let mut text = lines(std::iter::once(
+        balls
+            .iter()
+            .map(|ball| ball.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    ));
+    for _ in 0..rng.range(1, 5) {
+        let mut board: Vec<u64> = (0..numbers).collect();
+        rng.shuffle(&mut board);
+        text.push('\n');
+        text += &lines(board[..size * size].chunks(size).map(|row| {
+            row.iter()
+                .map(|number| format!("{:2}", number))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }));
+    }
+    text
+}
+
+pub(crate) fn reference_bingo(buffer: &str) -> Answers {
+    let mut sections = buffer.split("\n\n");
+    let balls: Vec<i64> = sections
+        .next()?
+        .trim()
+        .split(',')
+        .map(|ball| ball.parse().unwrap())
+        .collect();
+    let boards: Vec<Vec<Vec<i64>>> = sections
+        .map(|board| {
+            board
+                .lines()
+                .map(|row| row.split_whitespace().map(|n| n.parse().unwrap()).collect())
+                .collect()
+        })
+        .collect();
+    let mut drawn = vec![];
+    let mut won = vec![false; boards.len()];
+    let mut scores = vec![];
+    for &ball in balls.iter() {
+        drawn.push(ball);
+        for (board, won) in boards.iter().zip(won.iter_mut()) {
+            let marked = |number: &i64| drawn.contains(number);
+            let rows = board.iter().any(|row| row.iter().all(marked));
+            let columns = (0..board.len()).any(|j| board.iter().all(|row| marked(&row[j])));
+            if !*won && (rows || columns) {
+                *won = true;
+                let unmarked: i64 = board.iter().flatten().filter(|n| !marked(n)).sum();
+                scores.push(unmarked * ball);
+            }
+        }
+    }
+    // No board wins last while any board never wins
+    if won.contains(&false) {
+        return None;
+    }
+    Some((*scores.first()?, *scores.last()?))
+}
+
+// -----------------------------------------------------------------------------
+// Day 5
+// -----------------------------------------------------------------------------
+pub(crate) fn generate_segments(rng: &mut Rng) -> String {
+    let count = rng.range(1, 30);
+    lines((0..count).map(|_| {
+        // Starting at least 10 down keeps rising diagonals on the floor
+        let (x, y) = (rng.range(0, 20) as i64, rng.range(10, 30) as i64);
+        let length = rng.range(0, 10) as i64;
+        let (dx, dy) = [(1, 0), (0, 1), (1, 1), (1, -1)][rng.range(0, 3) as usize];
+        let (x2, y2) = (x + dx * length, y + dy * length);
+        if rng.one_in(2) {
+            format!("{},{} -> {},{}", x, y, x2, y2)
+        } else {
+            format!("{},{} -> {},{}", x2, y2, x, y)
+        }
+    }))
+}
+
+pub(crate) fn reference_segments(buffer: &str) -> Answers {
+    let mut axis: HashMap<(i64, i64), i64> = HashMap::new();
+    let mut all: HashMap<(i64, i64), i64> = HashMap::new();
+    for line in buffer.lines() {
+        let numbers: Vec<i64> = line
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse().unwrap())
+            .collect();
+        let (x1, y1, x2, y2) = (numbers[0], numbers[1], numbers[2], numbers[3]);
+        let steps = (x2 - x1).abs().max((y2 - y1).abs());
+        for step in 0..=steps {
+            let point = (
+                x1 + (x2 - x1).signum() * step,
+                y1 + (y2 - y1).signum() * step,
+            );
+            if x1 == x2 || y1 == y2 {
+                *axis.entry(point).or_insert(0) += 1;
+            }
+            *all.entry(point).or_insert(0) += 1;
+        }
+    }
+    let overlaps = |counts: &HashMap<(i64, i64), i64>| {
+        counts.values().filter(|&&count| count >= 2).count() as i64
+    };
+    Some((overlaps(&axis), overlaps(&all)))
+}
+
+// -----------------------------------------------------------------------------
+// Day 6
+// -----------------------------------------------------------------------------
+pub(crate) fn generate_fish(rng: &mut Rng) -> String {
+    let count = rng.range(1, 300);
+    let timers: Vec<String> = (0..count).map(|_| rng.range(0, 6).to_string()).collect();
+    timers.join(",") + "\n"
+}
+
+// Fish counted by timer, one day at a time
+pub(crate) fn simulate_fish(buffer: &str, generations: usize) -> u128 {
+    let mut counts = [0_u128; 9];
+    for timer in buffer.trim().split(',') {
+        counts[timer.parse::<usize>().unwrap()] += 1;
+    }
+    for _ in 0..generations {
+        let spawning = counts[0];
+        counts.rotate_left(1);
+        counts[6] += spawning;
+    }
+    counts.iter().sum()
+}
+
+// Every fish one by one, only for a few generations
+pub(crate) fn simulate_each_fish(buffer: &str, generations: usize) -> Vec<u128> {
+    let mut fish: Vec<u8> = buffer
+        .trim()
+        .split(',')
+        .map(|timer| timer.parse().unwrap())
+        .collect();
+    let mut counts = vec![fish.len() as u128];
+    for _ in 0..generations {
+        let spawning = fish.iter().filter(|&&timer| timer == 0).count();
+        fish.iter_mut()
+            .for_each(|timer| *timer = if *timer == 0 { 6 } else { *timer - 1 });
+        fish.extend(std::iter::repeat_n(8, spawning));
+        counts.push(fish.len() as u128);
+    }
+    counts
+}
+
+pub(crate) fn reference_fish(buffer: &str) -> Answers {
+    Some((
+        simulate_fish(buffer, 80) as i64,
+        simulate_fish(buffer, 256) as i64,
+    ))
+}
+
+// -----------------------------------------------------------------------------
+// Day 7
+// -----------------------------------------------------------------------------
+pub(crate) fn generate_crabs(rng: &mut Rng) -> String {
+    let count = rng.range(1, 200);
+    let spread = rng.range(0, 500);
+    let positions: Vec<String> = (0..count)
+        .map(|_| rng.range(0, spread).to_string())
+        .collect();
+    positions.join(",") + "\n"
+}
+
+pub(crate) fn reference_crabs(buffer: &str) -> Answers {
+    let positions: Vec<i64> = buffer
+        .trim()
+        .split(',')
+        .map(|position| position.parse().unwrap())
+        .collect();
+    let (min, max) = (*positions.iter().min()?, *positions.iter().max()?);
+    let cheapest = |cost: fn(i64) -> i64| {
+        (min..=max)
+            .map(|target| positions.iter().map(|p| cost((p - target).abs())).sum())
+            .min()
+            .unwrap()
+    };
+    Some((cheapest(|d| d), cheapest(|d| d * (d + 1) / 2)))
+}
+
+// -----------------------------------------------------------------------------
+// Day 8
+// -----------------------------------------------------------------------------
+const DIGITS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+pub(crate) fn generate_displays(rng: &mut Rng) -> String {
+    let count = rng.range(1, 4);
+    lines((0..count).map(|_| {
+        let mut wires: Vec<u8> = (b'a'..=b'g').collect();
+        rng.shuffle(&mut wires);
+        let scramble = |digit: usize, rng: &mut Rng| {
+            let mut pattern: Vec<u8> = DIGITS[digit]
+                .bytes()
+                .map(|segment| wires[(segment - b'a') as usize])
+                .collect();
+            rng.shuffle(&mut pattern);
+            String::from_utf8(pattern).unwrap()
+        };
+        let mut key: Vec<usize> = (0..10).collect();
+        rng.shuffle(&mut key);
+        let key: Vec<String> = key.iter().map(|&digit| scramble(digit, rng)).collect();
+        let output: Vec<String> = (0..4)
+            .map(|_| {
+                let digit = rng.range(0, 9) as usize;
+                scramble(digit, rng)
+            })
+            .collect();
+        format!("{} | {}", key.join(" "), output.join(" "))
+    }))
+}
+
+// Next permutation in lexicographic order, false after the last one
+fn next_permutation(items: &mut [u8]) -> bool {
+    let pivot = match (1..items.len()).rev().find(|&i| items[i - 1] < items[i]) {
+        Some(i) => i - 1,
+        None => return false,
+    };
+    let swap = (pivot + 1..items.len())
+        .rev()
+        .find(|&i| items[i] > items[pivot])
+        .unwrap();
+    items.swap(pivot, swap);
+    items[pivot + 1..].reverse();
+    true
+}
+
+// Try every wiring until one turns all patterns into digits
+pub(crate) fn reference_displays(buffer: &str) -> Answers {
+    let bits = |pattern: &str| pattern.bytes().fold(0_u8, |bits, b| bits | 1 << (b - b'a'));
+    let digits: Vec<u8> = DIGITS.iter().map(|digit| bits(digit)).collect();
+    let (mut simple, mut sum) = (0, 0);
+    for line in buffer.lines() {
+        let (key, output) = line.split_once(" | ")?;
+        let mut wiring: Vec<u8> = (0..7).collect();
+        let digit = |pattern: u8, wiring: &[u8]| {
+            let lit = (0..7)
+                .filter(|wire| pattern & 1 << wire != 0)
+                .fold(0_u8, |lit, wire| lit | 1 << wiring[wire]);
+            digits.iter().position(|&digit| digit == lit)
+        };
+        let key: Vec<u8> = key.split(' ').map(bits).collect();
+        while !key.iter().all(|&pattern| digit(pattern, &wiring).is_some()) {
+            if !next_permutation(&mut wiring) {
+                return None;
+            }
+        }
+        let value = output
+            .split(' ')
+            .map(|pattern| digit(bits(pattern), &wiring).unwrap())
+            .fold(0, |value, digit| {
+                simple += [1, 4, 7, 8].contains(&digit) as i64;
+                value * 10 + digit as i64
+            });
+        sum += value;
+    }
+    Some((simple, sum))
+}
+
+// -----------------------------------------------------------------------------
+// Day 9
+// -----------------------------------------------------------------------------
+// Walls of 9 split the map, and each basin slopes up from one random sink,
+// as the puzzle promises a single low point per basin
+pub(crate) fn generate_heightmap(rng: &mut Rng) -> String {
+    let (width, height) = (rng.range(1, 12) as usize, rng.range(1, 12) as usize);
+    let mut heights = vec![vec![9_u8; width]; height];
+    let mut open: Vec<(usize, usize)> = vec![];
+    for (y, row) in heights.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            if !rng.one_in(4) {
+                *cell = u8::MAX;
+                open.push((x, y));
+            }
+        }
+    }
+    rng.shuffle(&mut open);
+    for &(x, y) in open.iter() {
+        if heights[y][x] != u8::MAX {
+            continue;
+        }
+        // Height grows with the distance from the sink, up to 8
+        let mut queue = VecDeque::from(vec![(x, y, 0_u8)]);
+        heights[y][x] = 0;
+        while let Some((x, y, level)) = queue.pop_front() {
+            for (nx, ny) in neighbours(x, y, width, height) {
+                if heights[ny][nx] == u8::MAX {
+                    heights[ny][nx] = (level + 1).min(8);
+                    queue.push_back((nx, ny, level + 1));
+                }
+            }
+        }
+    }
+    lines(heights.iter().map(|row| {
+        row.iter()
+            .map(|&level| (b'0' + level) as char)
+            .collect::<String>()
+    }))
+}
+
+fn neighbours(x: usize, y: usize, width: usize, height: usize) -> Vec<(usize, usize)> {
+    let mut cells = vec![];
+    if x > 0 {
+        cells.push((x - 1, y));
+    }
+    if x + 1 < width {
+        cells.push((x + 1, y));
+    }
+    if y > 0 {
+        cells.push((x, y - 1));
+    }
+    if y + 1 < height {
+        cells.push((x, y + 1));
+    }
+    cells
+}
+
+pub(crate) fn reference_heightmap(buffer: &str) -> Answers {
+    let heights: Vec<Vec<u8>> = buffer
+        .lines()
+        .map(|line| line.bytes().map(|b| b - b'0').collect())
+        .collect();
+    let (width, height) = (heights[0].len(), heights.len());
+    let mut risk = 0;
+    let mut sizes = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            let level = heights[y][x];
+            // A 9 is wall even without neighbours
+            let low = level < 9
+                && neighbours(x, y, width, height)
+                    .iter()
+                    .all(|&(nx, ny)| heights[ny][nx] > level);
+            if !low {
+                continue;
+            }
+            risk += level as i64 + 1;
+            let mut seen = vec![(x, y)];
+            let mut queue = VecDeque::from(vec![(x, y)]);
+            while let Some((x, y)) = queue.pop_front() {
+                for (nx, ny) in neighbours(x, y, width, height) {
+                    if heights[ny][nx] != 9 && !seen.contains(&(nx, ny)) {
+                        seen.push((nx, ny));
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            sizes.push(seen.len() as i64);
+        }
+    }
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    Some((risk, sizes.iter().take(3).product()))
+}
+
+// -----------------------------------------------------------------------------
+// Day 10
+// -----------------------------------------------------------------------------
+const BRACKETS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+pub(crate) fn generate_brackets(rng: &mut Rng) -> String {
+    let count = rng.range(1, 12);
+    lines((0..count).map(|_| {
+        // Nested chunks, then maybe cut short or with one closer swapped
+        let mut line = vec![];
+        let mut open = vec![];
+        for _ in 0..rng.range(1, 40) {
+            if !open.is_empty() && rng.one_in(2) {
+                line.push(open.pop().unwrap());
+            } else {
+                let (opener, closer) = BRACKETS[rng.range(0, 3) as usize];
+                line.push(opener);
+                open.push(closer);
+            }
+        }
+        if rng.one_in(3) {
+            line.extend(open.iter().rev());
+        }
+        if rng.one_in(2) {
+            let i = rng.range(0, line.len() as u64 - 1) as usize;
+            line[i] = BRACKETS[rng.range(0, 3) as usize].1;
+        }
+        line.iter().collect::<String>()
+    }))
+}
+
+// Strip matched pairs until none are left, what stays decides the line
+pub(crate) fn reference_brackets(buffer: &str) -> Answers {
+    let mut corrupted = 0;
+    let mut completions = vec![];
+    for line in buffer.lines() {
+        let mut rest = line.to_string();
+        loop {
+            let before = rest.len();
+            for (opener, closer) in BRACKETS.iter() {
+                rest = rest.replace(&format!("{}{}", opener, closer), "");
+            }
+            if rest.len() == before {
+                break;
+            }
+        }
+        let score = |closer: char, scores: [i64; 4]| {
+            scores[BRACKETS.iter().position(|pair| pair.1 == closer).unwrap()]
+        };
+        match rest
+            .chars()
+            .find(|&c| BRACKETS.iter().any(|pair| pair.1 == c))
+        {
+            Some(closer) => corrupted += score(closer, [3, 57, 1197, 25137]),
+            None if !rest.is_empty() => {
+                let completion = rest.chars().rev().fold(0, |total, opener| {
+                    let closer = BRACKETS.iter().find(|pair| pair.0 == opener).unwrap().1;
+                    total * 5 + score(closer, [1, 2, 3, 4])
+                });
+                completions.push(completion);
+            }
+            None => (),
+        }
+    }
+    completions.sort_unstable();
+    Some((corrupted, *completions.get(completions.len() / 2)?))
+}
+
+// -----------------------------------------------------------------------------
+// Day 11
+// -----------------------------------------------------------------------------
+pub(crate) fn generate_octopuses(rng: &mut Rng) -> String {
+    let (width, height) = if rng.one_in(4) {
+        (10, 10)
+    } else {
+        (rng.range(1, 12), rng.range(1, 12))
+    };
+    lines((0..height).map(|_| {
+        (0..width)
+            .map(|_| (b'0' + rng.range(0, 9) as u8) as char)
+            .collect::<String>()
+    }))
+}
+
+// Flashes in each generation, one octopus at a time
+pub(crate) fn simulate_octopuses(buffer: &str, generations: usize) -> Vec<usize> {
+    let mut energy: Vec<Vec<u8>> = buffer
+        .lines()
+        .map(|line| line.bytes().map(|b| b - b'0').collect())
+        .collect();
+    let (width, height) = (energy[0].len() as i64, energy.len() as i64);
+    (0..generations)
+        .map(|_| {
+            let mut flashed = vec![vec![false; width as usize]; height as usize];
+            let mut pending = vec![];
+            for y in 0..height {
+                for x in 0..width {
+                    pending.push((x, y));
+                }
+            }
+            while let Some((x, y)) = pending.pop() {
+                let (i, j) = (x as usize, y as usize);
+                if flashed[j][i] {
+                    continue;
+                }
+                energy[j][i] += 1;
+                if energy[j][i] > 9 {
+                    flashed[j][i] = true;
+                    energy[j][i] = 0;
+                    for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+                        let (nx, ny) = (x + dx, y + dy);
+                        if (dx, dy) != (0, 0) && 0 <= nx && nx < width && 0 <= ny && ny < height {
+                            pending.push((nx, ny));
+                        }
+                    }
+                }
+            }
+            flashed.iter().flatten().filter(|&&flash| flash).count()
+        })
+        .collect()
+}
+
+// Only defined when the octopuses synchronize within the given generations
+pub(crate) fn reference_octopuses(buffer: &str, limit: usize) -> Answers {
+    let flashes = simulate_octopuses(buffer, limit);
+    let size = buffer.lines().map(|line| line.len()).sum();
+    let synchronized = flashes.iter().position(|&count| count == size)?;
+    Some((
+        flashes.iter().take(100).sum::<usize>() as i64,
+        synchronized as i64 + 1,
+    ))
+}
+
+// -----------------------------------------------------------------------------